@@ -0,0 +1,18 @@
+//! Fuzz the binary save decoder
+//!
+//! Any input must either be rejected with a `SaveError` or decode to a pet.
+//! Saves in the current format must encode back to exactly the same bytes.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use tamagochi_core::save::{FORMAT_VERSION, SAVE_SIZE};
+use tamagochi_core::Tamagochi;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(tama) = Tamagochi::from_bytes(data) {
+        if data[4] == FORMAT_VERSION {
+            assert_eq!(&tama.to_bytes()[..], &data[..SAVE_SIZE]);
+        }
+    }
+});
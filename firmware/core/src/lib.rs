@@ -7,14 +7,39 @@
 
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(feature = "serialization")]
+extern crate alloc;
+
+pub mod listener;
+pub mod minigame;
+mod mood;
+pub mod passcode;
+pub mod qr;
+mod rules;
+pub mod save;
+pub mod schema;
+pub mod storage;
+
+pub use listener::{GameSession, Listener, Stat};
+pub use minigame::{MiniGame, MiniGameKind, MiniGameResult};
+pub use mood::Mood;
+pub use passcode::{Passcode, PasscodeError};
+pub use qr::QrCode;
+pub use rules::{Action, ActionEffect, DecayRates};
+pub use save::SaveError;
+pub use schema::SCHEMA_VERSION;
+
 #[cfg(feature = "serialization")]
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
 
 /// Main Tamagochi state representing the digital pet
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 pub struct Tamagochi {
-    /// Hunger level (0 = starving, 100 = full)
+    /// Hunger level (0 = full, 100 = starving)
     pub hunger: u8,
     
     /// Happiness level (0 = very sad, 100 = very happy)
@@ -34,11 +59,54 @@ pub struct Tamagochi {
     
     /// Evolution quality score (affects which adult form)
     pub care_quality: u8,
+
+    /// Weight in grams (meals add weight, exercise burns it)
+    pub weight: u8,
+
+    /// Number of times a need was allowed to become critical
+    pub care_mistakes: u8,
+
+    /// Ticks spent as an adult before becoming an elder
+    pub adult_duration: u32,
+
+    /// Egg temperature (0 = cold, 100 = cosy), only relevant before hatching
+    pub warmth: u8,
+
+    /// Ticks the egg has spent warm enough to incubate
+    pub incubation: u16,
+
+    /// Current mood (see [`Mood`])
+    pub mood: Mood,
+
+    /// Age at which the current mood started
+    pub mood_since: u32,
+
+    /// Generation of the pet's family line (the first pet is 1)
+    pub generation: u16,
 }
 
+/// Default time spent in the adult stage (14 days)
+pub const DEFAULT_ADULT_DURATION: u32 = 1_209_600;
+
+/// Age at which an unattended egg hatches (1 hour)
+const HATCH_AGE: u32 = 3600;
+
+/// Warmth needed for the egg to make incubation progress
+const INCUBATION_WARMTH: u8 = 40;
+
+/// Incubation ticks of a perfectly attended egg
+const FULL_INCUBATION: u32 = 2400;
+
+/// Age at which a teenager becomes an adult (7 days)
+const ADULT_AGE: u32 = 604_800;
+
+/// Base time an elder lives before passing away (3 days)
+const ELDER_BASE_LIFESPAN: u32 = 259_200;
+
 /// Life stages of the Tamagochi
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 pub enum LifeStage {
     /// Freshly hatched egg
     Egg,
@@ -50,11 +118,14 @@ pub enum LifeStage {
     Teenager,
     /// Adult (7+ days) - final form depends on care
     Adult(AdultForm),
+    /// Elder (after the adult duration) - slower, lives out its lifespan
+    Elder,
 }
 
 /// Different adult forms based on care quality
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 pub enum AdultForm {
     /// Poor care (care_quality < 30)
     Neglected,
@@ -66,11 +137,17 @@ pub enum AdultForm {
 
 /// Events that can occur during gameplay
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 pub enum GameEvent {
+    /// The egg hatched into a baby
+    Hatched,
     /// Tamagochi evolved to a new stage
     Evolution(LifeStage),
-    /// Tamagochi died
+    /// Tamagochi died from neglect (health reached zero)
     Death,
+    /// Tamagochi passed away peacefully of old age
+    PassedAway,
     /// Needs attention (hunger/happiness critical)
     NeedsAttention,
     /// Birthday/age milestone
@@ -88,6 +165,31 @@ impl Tamagochi {
             stage: LifeStage::Egg,
             alive: true,
             care_quality: 50,
+            weight: 5,
+            care_mistakes: 0,
+            adult_duration: DEFAULT_ADULT_DURATION,
+            warmth: 50,
+            incubation: 0,
+            mood: Mood::Content,
+            mood_since: 0,
+            generation: 1,
+        }
+    }
+
+    /// A new egg continuing this pet's family line
+    pub fn next_generation(&self) -> Self {
+        Self {
+            generation: self.generation.saturating_add(1),
+            adult_duration: self.adult_duration,
+            ..Self::new()
+        }
+    }
+
+    /// Create a new Tamagochi with a custom adult stage duration
+    pub fn with_adult_duration(adult_duration: u32) -> Self {
+        Self {
+            adult_duration,
+            ..Self::new()
         }
     }
 
@@ -96,58 +198,106 @@ impl Tamagochi {
     /// Reduces hunger, slightly increases happiness.
     /// Overfeeding can reduce happiness.
     pub fn feed(&mut self) {
-        if !self.alive {
-            return;
-        }
-
-        if self.hunger > 20 {
-            // Normal feeding
-            self.hunger = self.hunger.saturating_sub(20);
-            self.happiness = self.happiness.saturating_add(5).min(100);
-            self.update_care_quality(2);
-        } else {
-            // Overfeeding - slight penalty
-            self.hunger = 0;
-            self.happiness = self.happiness.saturating_sub(3);
-        }
+        self.perform(Action::Feed);
     }
 
     /// Play with the Tamagochi
     /// 
     /// Increases happiness significantly but also increases hunger.
     pub fn play(&mut self) {
-        if !self.alive {
-            return;
-        }
-
-        self.happiness = self.happiness.saturating_add(15).min(100);
-        self.hunger = self.hunger.saturating_add(5).min(100);
-        self.update_care_quality(3);
+        self.perform(Action::Play);
     }
 
     /// Give medicine to the Tamagochi
     /// 
     /// Restores health but may reduce happiness.
     pub fn medicine(&mut self) {
-        if !self.alive {
-            return;
-        }
-
-        self.health = self.health.saturating_add(30).min(100);
-        self.happiness = self.happiness.saturating_sub(5);
-        self.update_care_quality(1);
+        self.perform(Action::Medicine);
     }
 
     /// Clean/care for the Tamagochi
     /// 
     /// Small happiness boost, demonstrates good care.
     pub fn clean(&mut self) {
+        self.perform(Action::Clean);
+    }
+
+    /// Keep the egg warm
+    ///
+    /// Warming while the egg wiggles is twice as effective.
+    pub fn warm_egg(&mut self) {
+        self.perform(Action::Warm);
+    }
+
+    /// Whether the egg is currently wiggling and wants attention
+    ///
+    /// The egg wiggles for 10 ticks every 5 minutes.
+    pub fn is_wiggling(&self) -> bool {
+        self.alive && self.stage == LifeStage::Egg && self.age % 300 >= 290
+    }
+
+    /// Whether an action is available right now
+    pub fn can_perform(&self, action: Action) -> bool {
+        self.alive && self.stage.allows(action)
+    }
+
+    /// Perform an action using the current stage's effect table
+    ///
+    /// Returns `false` if the action is not available in this stage.
+    pub fn perform(&mut self, action: Action) -> bool {
         if !self.alive {
-            return;
+            return false;
+        }
+        let Some(effect) = self.stage.action_effect(action) else {
+            return false;
+        };
+
+        if action == Action::Warm {
+            // The care it counts for comes from the effect table below
+            let warmth = if self.is_wiggling() { 30 } else { 15 };
+            self.warmth = self.warmth.saturating_add(warmth).min(100);
+        }
+
+        if action == Action::Feed && self.hunger <= effect.hunger.unsigned_abs() {
+            // Overfeeding - slight penalty
+            self.hunger = 0;
+            self.happiness = self.happiness.saturating_sub(3);
+            self.update_weight(effect.weight);
+            self.update_mood();
+            return true;
         }
 
-        self.happiness = self.happiness.saturating_add(10).min(100);
-        self.update_care_quality(2);
+        self.hunger = apply_delta(self.hunger, effect.hunger);
+        self.happiness = apply_delta(self.happiness, effect.happiness);
+        self.health = apply_delta(self.health, effect.health);
+        self.update_care_quality(effect.care as i16);
+        self.update_weight(effect.weight);
+        self.update_mood();
+        true
+    }
+
+    /// Apply the outcome of a finished mini-game
+    ///
+    /// Playing a game counts as playing, but the happiness gained and the
+    /// weight burned depend on how many rounds were won. Returns `false`
+    /// if playing is not available in this stage.
+    pub fn apply_minigame(&mut self, result: MiniGameResult) -> bool {
+        if !self.can_perform(Action::Play) {
+            return false;
+        }
+        let Some(effect) = self.stage.action_effect(Action::Play) else {
+            return false;
+        };
+
+        // Results are plain data, so don't trust them beyond a real game
+        let wins = result.wins.min(result.rounds).min(minigame::ROUNDS) as i16;
+        let happiness = (effect.happiness as i16 * (1 + wins) / 4) as i8;
+        self.hunger = apply_delta(self.hunger, effect.hunger);
+        self.happiness = apply_delta(self.happiness, happiness);
+        self.update_care_quality(effect.care as i16);
+        self.update_weight(effect.weight - (wins / 2) as i8);
+        self.update_mood();
+        true
     }
 
     /// Update game state (call this periodically, e.g., every second)
@@ -160,9 +310,28 @@ impl Tamagochi {
 
         self.age = self.age.saturating_add(1);
 
-        // Natural stat degradation
-        self.hunger = self.hunger.saturating_add(1);
-        self.happiness = self.happiness.saturating_sub(1);
+        let was_critical = self.needs_status();
+
+        // The egg slowly cools down and only incubates while warm
+        if self.stage == LifeStage::Egg {
+            self.warmth = self.warmth.saturating_sub(rules::step(1, self.age));
+            if self.warmth >= INCUBATION_WARMTH {
+                self.incubation = self.incubation.saturating_add(1);
+            }
+        }
+
+        // Natural stat degradation, depending on the life stage
+        let rates = self.stage.decay_rates();
+        self.hunger = self.hunger.saturating_add(rates.hunger_step(self.age)).min(100);
+        self.happiness = self.happiness.saturating_sub(rates.happiness_step(self.age));
+
+        // A need turning critical counts as a care mistake
+        let now_critical = self.needs_status();
+        if (now_critical.hunger_critical && !was_critical.hunger_critical)
+            || (now_critical.happiness_critical && !was_critical.happiness_critical)
+        {
+            self.care_mistakes = self.care_mistakes.saturating_add(1);
+        }
 
         // Health degradation based on needs
         if self.hunger > 80 {
@@ -174,6 +343,14 @@ impl Tamagochi {
             self.update_care_quality(-3);
         }
 
+        self.update_mood();
+
+        // Check for hatching
+        if self.stage == LifeStage::Egg && self.age + self.incubation as u32 / 2 >= HATCH_AGE {
+            self.hatch();
+            return Some(GameEvent::Hatched);
+        }
+
         // Check for evolution
         if let Some(new_stage) = self.check_evolution() {
             self.stage = new_stage;
@@ -186,13 +363,19 @@ impl Tamagochi {
             return Some(GameEvent::Death);
         }
 
+        // Check for natural death of old age
+        if self.stage == LifeStage::Elder && self.age >= self.lifespan() {
+            self.alive = false;
+            return Some(GameEvent::PassedAway);
+        }
+
         // Check if needs attention
         if self.hunger > 80 || self.happiness < 20 || self.health < 30 {
             return Some(GameEvent::NeedsAttention);
         }
 
         // Birthday milestones (every 24 "hours" = 86400 ticks if tick = 1 second)
-        if self.age > 0 && self.age % 86400 == 0 {
+        if self.age > 0 && self.age.is_multiple_of(86400) {
             return Some(GameEvent::Birthday(self.age / 86400));
         }
 
@@ -202,10 +385,6 @@ impl Tamagochi {
     /// Check if Tamagochi should evolve to next stage
     fn check_evolution(&mut self) -> Option<LifeStage> {
         match self.stage {
-            LifeStage::Egg if self.age >= 3600 => {
-                // Egg hatches after 1 hour (3600 ticks)
-                Some(LifeStage::Baby)
-            }
             LifeStage::Baby if self.age >= 86400 => {
                 // Baby becomes child after 24 hours
                 Some(LifeStage::Child)
@@ -214,7 +393,7 @@ impl Tamagochi {
                 // Child becomes teenager after 3 days
                 Some(LifeStage::Teenager)
             }
-            LifeStage::Teenager if self.age >= 604800 => {
+            LifeStage::Teenager if self.age >= ADULT_AGE => {
                 // Teenager becomes adult after 7 days
                 let form = match self.care_quality {
                     0..=30 => AdultForm::Neglected,
@@ -223,16 +402,86 @@ impl Tamagochi {
                 };
                 Some(LifeStage::Adult(form))
             }
+            LifeStage::Adult(_) if self.age >= self.elder_age() => {
+                // Adult grows old after the configured adult duration
+                Some(LifeStage::Elder)
+            }
             _ => None,
         }
     }
 
+    /// Run several updates in a row, e.g. to catch up on time passed
+    /// while the game wasn't running
+    ///
+    /// Every event is passed to `on_event` together with the Tamagochi
+    /// right after it. Stops early once the Tamagochi is no longer alive;
+    /// returns the number of ticks run.
+    pub fn advance(&mut self, ticks: u32, mut on_event: impl FnMut(GameEvent, &Tamagochi)) -> u32 {
+        for tick in 0..ticks {
+            if !self.alive {
+                return tick;
+            }
+            if let Some(event) = self.update() {
+                on_event(event, self);
+            }
+        }
+        ticks
+    }
+
+    /// Hatch the egg into a baby
+    ///
+    /// A well-incubated egg hatches into a happier, healthier baby.
+    fn hatch(&mut self) {
+        let care = (self.incubation as u32 * 100 / FULL_INCUBATION).min(100) as u8;
+        self.stage = LifeStage::Baby;
+        self.happiness = 30 + care * 2 / 5;
+        self.health = 60 + care * 2 / 5;
+        self.update_care_quality(care as i16 / 10);
+    }
+
+    /// Ticks left until the egg hatches, assuming it stays warm
+    pub fn ticks_until_hatch(&self) -> Option<u32> {
+        if self.stage != LifeStage::Egg {
+            return None;
+        }
+        let remaining = HATCH_AGE.saturating_sub(self.age + self.incubation as u32 / 2);
+        // Each warm tick counts one and a half
+        Some((remaining * 2).div_ceil(3))
+    }
+
+    /// Age at which the adult becomes an elder
+    pub fn elder_age(&self) -> u32 {
+        ADULT_AGE.saturating_add(self.adult_duration)
+    }
+
+    /// Natural lifespan in ticks
+    ///
+    /// Good care extends the time spent as an elder by up to 3 days,
+    /// while every care mistake shortens it by 6 hours. An elder always
+    /// gets at least one day.
+    pub fn lifespan(&self) -> u32 {
+        let bonus = self.care_quality as u32 * 2592;
+        let penalty = self.care_mistakes as u32 * 21600;
+        let elder_span = (ELDER_BASE_LIFESPAN + bonus).saturating_sub(penalty).max(86400);
+        self.elder_age().saturating_add(elder_span)
+    }
+
+    /// Whether the Tamagochi died of old age rather than neglect
+    pub fn passed_away(&self) -> bool {
+        !self.alive && self.health > 0
+    }
+
     /// Update care quality score
     fn update_care_quality(&mut self, delta: i16) {
         let new_quality = (self.care_quality as i16) + delta;
         self.care_quality = new_quality.clamp(0, 100) as u8;
     }
 
+    /// Update weight, never dropping below 1 gram
+    fn update_weight(&mut self, delta: i8) {
+        self.weight = (self.weight as i16 + delta as i16).clamp(1, 99) as u8;
+    }
+
     /// Get current needs status
     pub fn needs_status(&self) -> NeedsStatus {
         NeedsStatus {
@@ -252,10 +501,17 @@ impl Tamagochi {
             happiness_level: self.happiness,
             health_level: self.health,
             care_rating: self.care_quality,
+            weight: self.weight,
+            mood: self.mood,
         }
     }
 }
 
+/// Apply a signed change to a 0-100 stat
+fn apply_delta(value: u8, delta: i8) -> u8 {
+    (value as i16 + delta as i16).clamp(0, 100) as u8
+}
+
 impl Default for Tamagochi {
     fn default() -> Self {
         Self::new()
@@ -264,6 +520,8 @@ impl Default for Tamagochi {
 
 /// Status of critical needs
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 pub struct NeedsStatus {
     pub hunger_critical: bool,
     pub happiness_critical: bool,
@@ -273,6 +531,7 @@ pub struct NeedsStatus {
 /// High-level status summary
 #[derive(Clone, Copy, Debug)]
 #[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
 pub struct StatusSummary {
     pub alive: bool,
     pub stage: LifeStage,
@@ -281,6 +540,8 @@ pub struct StatusSummary {
     pub happiness_level: u8,
     pub health_level: u8,
     pub care_rating: u8,
+    pub weight: u8,
+    pub mood: Mood,
 }
 
 #[cfg(test)]
@@ -298,20 +559,162 @@ mod tests {
     #[test]
     fn test_feeding() {
         let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Baby;
         let initial_hunger = tama.hunger;
         tama.feed();
         assert!(tama.hunger < initial_hunger);
     }
 
     #[test]
-    fn test_evolution() {
+    fn test_egg_cannot_be_fed() {
+        let mut tama = Tamagochi::new();
+        let initial_hunger = tama.hunger;
+        assert!(!tama.can_perform(Action::Feed));
+        tama.feed();
+        assert_eq!(tama.hunger, initial_hunger);
+    }
+
+    #[test]
+    fn test_baby_gets_hungry_faster() {
+        let mut baby = Tamagochi::new();
+        baby.stage = LifeStage::Baby;
+        let mut adult = baby;
+        adult.stage = LifeStage::Adult(AdultForm::Normal);
+        for _ in 0..10 {
+            baby.update();
+            adult.update();
+        }
+        assert!(baby.hunger > adult.hunger);
+    }
+
+    #[test]
+    fn test_minigame_wins_matter() {
+        let mut winner = Tamagochi::new();
+        winner.stage = LifeStage::Child;
+        winner.happiness = 30;
+        winner.weight = 20;
+        let mut loser = winner;
+        assert!(winner.apply_minigame(MiniGameResult { wins: 5, rounds: 5 }));
+        assert!(loser.apply_minigame(MiniGameResult { wins: 0, rounds: 5 }));
+        assert!(winner.happiness > loser.happiness);
+        assert!(winner.weight < loser.weight);
+
+        let mut egg = Tamagochi::new();
+        assert!(!egg.apply_minigame(MiniGameResult { wins: 5, rounds: 5 }));
+    }
+
+    #[test]
+    fn test_overfeeding_updates_the_mood() {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Child;
+        tama.hunger = 10;
+        tama.happiness = 21;
+        tama.mood = Mood::Content;
+        assert!(tama.perform(Action::Feed));
+        assert_eq!(tama.hunger, 0);
+        assert_eq!(tama.mood, Mood::Sad);
+    }
+
+    #[test]
+    fn test_impossible_minigame_results_still_please() {
+        for result in [MiniGameResult { wins: 40, rounds: 40 }, MiniGameResult { wins: 255, rounds: 255 }] {
+            let mut tama = Tamagochi::new();
+            tama.stage = LifeStage::Teenager;
+            tama.happiness = 50;
+            tama.weight = 20;
+            let mut perfect = tama;
+            assert!(tama.apply_minigame(result));
+            assert!(perfect.apply_minigame(MiniGameResult { wins: minigame::ROUNDS, rounds: minigame::ROUNDS }));
+            assert!(tama.happiness > 50, "{:?}", result);
+            assert_eq!(tama.happiness, perfect.happiness);
+            assert_eq!(tama.weight, perfect.weight);
+        }
+    }
+
+    #[test]
+    fn test_hatching() {
         let mut tama = Tamagochi::new();
         tama.age = 3600; // 1 hour
         let event = tama.update();
-        assert!(matches!(event, Some(GameEvent::Evolution(LifeStage::Baby))));
+        assert!(matches!(event, Some(GameEvent::Hatched)));
         assert_eq!(tama.stage, LifeStage::Baby);
     }
 
+    #[test]
+    fn test_advance_reports_events_and_stops_at_death() {
+        let mut tama = Tamagochi::new();
+        tama.age = 3590;
+        let mut events = Vec::new();
+        assert_eq!(tama.advance(20, |event, tama| events.push((event, tama.age))), 20);
+        assert!(events.iter().any(|&(event, age)| matches!(event, GameEvent::Hatched) && (3591..=3610).contains(&age)));
+
+        tama.health = 0;
+        tama.hunger = 100;
+        let ran = tama.advance(1000, |_, _| {});
+        assert!(!tama.alive);
+        assert!(ran < 1000);
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn test_events_serialize() {
+        let events = [
+            GameEvent::Hatched,
+            GameEvent::Evolution(LifeStage::Adult(AdultForm::Premium)),
+            GameEvent::Birthday(3),
+        ];
+        let json = serde_json::to_string(&events).unwrap();
+        assert_eq!(json, r#"["Hatched",{"Evolution":{"Adult":"Premium"}},{"Birthday":3}]"#);
+        let back: Vec<GameEvent> = serde_json::from_str(&json).unwrap();
+        assert!(matches!(back[1], GameEvent::Evolution(LifeStage::Adult(AdultForm::Premium))));
+    }
+
+    #[test]
+    fn test_warm_egg_hatches_sooner_and_healthier() {
+        let mut cold = Tamagochi::new();
+        cold.warmth = 0;
+        let mut warm = Tamagochi::new();
+        let mut warm_hatched_at = None;
+        for tick in 0..3600 {
+            if warm.warmth < 60 {
+                warm.warm_egg();
+            }
+            if matches!(warm.update(), Some(GameEvent::Hatched)) {
+                warm_hatched_at = Some(tick);
+                break;
+            }
+            assert!(cold.update().is_none());
+        }
+        assert!(warm_hatched_at.unwrap() < 3000);
+
+        cold.age = 3599;
+        assert!(matches!(cold.update(), Some(GameEvent::Hatched)));
+        assert!(warm.health > cold.health);
+        assert!(warm.happiness > cold.happiness);
+    }
+
+    #[test]
+    fn test_warming_wiggling_egg() {
+        let mut tama = Tamagochi::new();
+        tama.warmth = 0;
+        tama.age = 295;
+        assert!(tama.is_wiggling());
+        let care = tama.care_quality;
+        tama.warm_egg();
+        assert_eq!(tama.warmth, 30);
+        assert_eq!(tama.care_quality, care + 1, "warming counts as care once");
+    }
+
+    #[test]
+    fn test_evolution() {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Baby;
+        tama.age = 86399;
+        let event = tama.update();
+        assert!(matches!(event, Some(GameEvent::Evolution(LifeStage::Child))));
+        assert_eq!(tama.stage, LifeStage::Child);
+    }
+
     #[test]
     fn test_death_from_health() {
         let mut tama = Tamagochi::new();
@@ -332,4 +735,57 @@ mod tests {
         assert!(matches!(event, Some(GameEvent::Evolution(LifeStage::Adult(AdultForm::Premium)))));
         assert!(matches!(tama.stage, LifeStage::Adult(AdultForm::Premium)));
     }
+
+    #[test]
+    fn test_adult_becomes_elder() {
+        let mut tama = Tamagochi::with_adult_duration(1000);
+        tama.stage = LifeStage::Adult(AdultForm::Normal);
+        tama.age = tama.elder_age() - 1;
+        let event = tama.update();
+        assert!(matches!(event, Some(GameEvent::Evolution(LifeStage::Elder))));
+        assert_eq!(tama.stage, LifeStage::Elder);
+    }
+
+    #[test]
+    fn test_elder_passes_away_naturally() {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Elder;
+        tama.age = tama.lifespan() - 1;
+        let event = tama.update();
+        assert!(matches!(event, Some(GameEvent::PassedAway)));
+        assert!(!tama.alive);
+        assert!(tama.passed_away());
+    }
+
+    #[test]
+    fn test_care_affects_lifespan() {
+        let mut good = Tamagochi::new();
+        good.care_quality = 100;
+        let mut poor = Tamagochi::new();
+        poor.care_quality = 100;
+        poor.care_mistakes = 4;
+        assert!(good.lifespan() > poor.lifespan());
+    }
+
+    #[test]
+    fn test_care_mistake_counted_once() {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Child;
+        tama.hunger = 80;
+        tama.update();
+        tama.update();
+        assert_eq!(tama.care_mistakes, 1);
+    }
+
+    #[test]
+    fn test_elder_recovers_slowly() {
+        let mut adult = Tamagochi::new();
+        adult.stage = LifeStage::Adult(AdultForm::Normal);
+        adult.health = 50;
+        let mut elder = adult;
+        elder.stage = LifeStage::Elder;
+        adult.medicine();
+        elder.medicine();
+        assert!(elder.health < adult.health);
+    }
 }
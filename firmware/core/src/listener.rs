@@ -0,0 +1,288 @@
+//! Reacting to what happens to the pet
+//!
+//! Rather than polling the return value of [`Tamagochi::update`], a
+//! frontend implements [`Listener`] once per concern (sound, logging, the
+//! screen, saving) and drives the pet through a [`GameSession`], which
+//! tells every listener about events, actions and stat changes.
+//!
+//! Listeners are combined by putting them in a tuple, e.g.
+//! `GameSession::new(tama, (Sound, Screen, Log))`; they are called in
+//! order. Every listener keeps its own type, so the calls are statically
+//! dispatched and nothing is allocated. `&mut L` and `Option<L>` are
+//! listeners too, for borrowed or optional ones.
+
+use crate::{Action, GameEvent, MiniGameResult, Tamagochi};
+
+/// A stat reported to [`Listener::on_stat_change`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stat {
+    Hunger,
+    Happiness,
+    Health,
+    Weight,
+}
+
+impl Stat {
+    /// All reported stats
+    pub const ALL: [Stat; 4] = [Stat::Hunger, Stat::Happiness, Stat::Health, Stat::Weight];
+
+    /// The stat's current value
+    pub fn of(self, tama: &Tamagochi) -> u8 {
+        match self {
+            Stat::Hunger => tama.hunger,
+            Stat::Happiness => tama.happiness,
+            Stat::Health => tama.health,
+            Stat::Weight => tama.weight,
+        }
+    }
+}
+
+/// Reacts to what happens in a [`GameSession`]
+///
+/// Every method does nothing by default, so a listener only implements
+/// what it cares about. `tama` is always the pet as it is afterwards.
+pub trait Listener {
+    /// A tick produced an event
+    fn on_event(&mut self, _event: GameEvent, _tama: &Tamagochi) {}
+
+    /// The player tried an action; `performed` is false if it wasn't
+    /// available
+    fn on_action(&mut self, _action: Action, _performed: bool, _tama: &Tamagochi) {}
+
+    /// A stat changed from `old` to `new`, through a tick or an action
+    fn on_stat_change(&mut self, _stat: Stat, _old: u8, _new: u8, _tama: &Tamagochi) {}
+}
+
+/// No listener at all
+impl Listener for () {}
+
+impl<L: Listener + ?Sized> Listener for &mut L {
+    fn on_event(&mut self, event: GameEvent, tama: &Tamagochi) {
+        (**self).on_event(event, tama);
+    }
+
+    fn on_action(&mut self, action: Action, performed: bool, tama: &Tamagochi) {
+        (**self).on_action(action, performed, tama);
+    }
+
+    fn on_stat_change(&mut self, stat: Stat, old: u8, new: u8, tama: &Tamagochi) {
+        (**self).on_stat_change(stat, old, new, tama);
+    }
+}
+
+impl<L: Listener> Listener for Option<L> {
+    fn on_event(&mut self, event: GameEvent, tama: &Tamagochi) {
+        if let Some(listener) = self {
+            listener.on_event(event, tama);
+        }
+    }
+
+    fn on_action(&mut self, action: Action, performed: bool, tama: &Tamagochi) {
+        if let Some(listener) = self {
+            listener.on_action(action, performed, tama);
+        }
+    }
+
+    fn on_stat_change(&mut self, stat: Stat, old: u8, new: u8, tama: &Tamagochi) {
+        if let Some(listener) = self {
+            listener.on_stat_change(stat, old, new, tama);
+        }
+    }
+}
+
+/// Tuples of listeners call each of them in order
+macro_rules! tuple_listener {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: Listener),+> Listener for ($($name,)+) {
+            fn on_event(&mut self, event: GameEvent, tama: &Tamagochi) {
+                $(self.$index.on_event(event, tama);)+
+            }
+
+            fn on_action(&mut self, action: Action, performed: bool, tama: &Tamagochi) {
+                $(self.$index.on_action(action, performed, tama);)+
+            }
+
+            fn on_stat_change(&mut self, stat: Stat, old: u8, new: u8, tama: &Tamagochi) {
+                $(self.$index.on_stat_change(stat, old, new, tama);)+
+            }
+        }
+    };
+}
+
+tuple_listener!(A 0);
+tuple_listener!(A 0, B 1);
+tuple_listener!(A 0, B 1, C 2);
+tuple_listener!(A 0, B 1, C 2, D 3);
+tuple_listener!(A 0, B 1, C 2, D 3, E 4);
+tuple_listener!(A 0, B 1, C 2, D 3, E 4, F 5);
+
+/// A pet and the listeners that react to it
+///
+/// Ticks and actions go through the session, which reports them: a tick
+/// first reports the stats it changed, then its event; an action is
+/// reported first, then the stats it changed.
+#[derive(Debug)]
+pub struct GameSession<L> {
+    tama: Tamagochi,
+    listener: L,
+}
+
+impl<L: Listener> GameSession<L> {
+    pub fn new(tama: Tamagochi, listener: L) -> Self {
+        Self { tama, listener }
+    }
+
+    pub fn tama(&self) -> &Tamagochi {
+        &self.tama
+    }
+
+    /// The pet, to change it directly; such changes aren't reported
+    pub fn tama_mut(&mut self) -> &mut Tamagochi {
+        &mut self.tama
+    }
+
+    pub fn listener(&self) -> &L {
+        &self.listener
+    }
+
+    pub fn listener_mut(&mut self) -> &mut L {
+        &mut self.listener
+    }
+
+    pub fn into_parts(self) -> (Tamagochi, L) {
+        (self.tama, self.listener)
+    }
+
+    /// Run one tick, see [`Tamagochi::update`]
+    pub fn update(&mut self) -> Option<GameEvent> {
+        let before = self.stats();
+        let event = self.tama.update();
+        self.report_stats(before);
+        if let Some(event) = event {
+            self.listener.on_event(event, &self.tama);
+        }
+        event
+    }
+
+    /// Run several ticks, see [`Tamagochi::advance`]
+    pub fn advance(&mut self, ticks: u32) -> u32 {
+        for tick in 0..ticks {
+            if !self.tama.alive {
+                return tick;
+            }
+            self.update();
+        }
+        ticks
+    }
+
+    /// Perform an action, see [`Tamagochi::perform`]
+    pub fn perform(&mut self, action: Action) -> bool {
+        let before = self.stats();
+        let performed = self.tama.perform(action);
+        self.listener.on_action(action, performed, &self.tama);
+        self.report_stats(before);
+        performed
+    }
+
+    /// Apply a finished mini-game, reported as [`Action::Play`]; see
+    /// [`Tamagochi::apply_minigame`]
+    pub fn apply_minigame(&mut self, result: MiniGameResult) -> bool {
+        let before = self.stats();
+        let performed = self.tama.apply_minigame(result);
+        self.listener.on_action(Action::Play, performed, &self.tama);
+        self.report_stats(before);
+        performed
+    }
+
+    fn stats(&self) -> [u8; Stat::ALL.len()] {
+        Stat::ALL.map(|stat| stat.of(&self.tama))
+    }
+
+    fn report_stats(&mut self, before: [u8; Stat::ALL.len()]) {
+        for (stat, old) in Stat::ALL.into_iter().zip(before) {
+            let new = stat.of(&self.tama);
+            if new != old {
+                self.listener.on_stat_change(stat, old, new, &self.tama);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::LifeStage;
+
+    /// Writes down everything it hears
+    #[derive(Default)]
+    struct Recorder {
+        heard: Vec<String>,
+    }
+
+    impl Listener for Recorder {
+        fn on_event(&mut self, event: GameEvent, _tama: &Tamagochi) {
+            self.heard.push(format!("{:?}", event));
+        }
+
+        fn on_action(&mut self, action: Action, performed: bool, _tama: &Tamagochi) {
+            self.heard.push(format!("{:?} {}", action, performed));
+        }
+
+        fn on_stat_change(&mut self, stat: Stat, old: u8, new: u8, _tama: &Tamagochi) {
+            self.heard.push(format!("{:?} {}->{}", stat, old, new));
+        }
+    }
+
+    /// Only counts events
+    #[derive(Default)]
+    struct Counter(u32);
+
+    impl Listener for Counter {
+        fn on_event(&mut self, _event: GameEvent, _tama: &Tamagochi) {
+            self.0 += 1;
+        }
+    }
+
+    #[test]
+    fn test_actions_and_their_stat_changes() {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Child;
+        let mut session = GameSession::new(tama, Recorder::default());
+
+        assert!(session.perform(Action::Feed));
+        assert!(!session.perform(Action::Warm));
+        assert_eq!(
+            session.listener().heard,
+            ["Feed true", "Hunger 50->30", "Happiness 50->55", "Weight 5->6", "Warm false"]
+        );
+    }
+
+    #[test]
+    fn test_ticks_reach_every_listener_in_order() {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Baby;
+        tama.hunger = 79;
+        let mut counter = Counter::default();
+        let mut session = GameSession::new(tama, (Recorder::default(), &mut counter, None::<Counter>));
+
+        assert!(matches!(session.update(), Some(GameEvent::NeedsAttention)));
+        let (tama, (recorder, _, _)) = session.into_parts();
+        assert_eq!(
+            recorder.heard,
+            ["Hunger 79->81", "Happiness 50->49", "Health 100->98", "NeedsAttention"]
+        );
+        assert_eq!(counter.0, 1);
+        assert_eq!(tama.age, 1);
+    }
+
+    #[test]
+    fn test_advance_stops_at_death() {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Baby;
+        let mut session = GameSession::new(tama, Recorder::default());
+        let ran = session.advance(1000);
+        assert!(ran < 1000);
+        assert!(!session.tama().alive);
+        assert_eq!(session.listener().heard.last().unwrap(), "Death");
+    }
+}
@@ -0,0 +1,306 @@
+//! Mini-games played with the Tamagochi
+//!
+//! Games are driven by abstract inputs and a millisecond clock supplied by
+//! the frontend, so the same rules run in a terminal and on the device.
+//! When a game is finished its [`MiniGameResult`] is handed to
+//! [`Tamagochi::apply_minigame`](crate::Tamagochi::apply_minigame).
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+/// Number of rounds in every mini-game
+pub const ROUNDS: u8 = 5;
+
+/// How long a round result stays on screen (ms)
+const REVEAL_MS: u32 = 1000;
+
+/// Shortest and longest wait before the reaction signal (ms)
+const SIGNAL_DELAY_MS: (u32, u32) = (1000, 3000);
+
+/// Time allowed to react after the signal (ms)
+const REACTION_WINDOW_MS: u32 = 700;
+
+/// Presses faster than this after the signal were made before it (ms)
+const MIN_REACTION_MS: u32 = 100;
+
+/// Abstract player input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum Input {
+    /// Choose left
+    Left,
+    /// Choose right
+    Right,
+    /// Press any button
+    Press,
+}
+
+/// A direction the pet can turn
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum Direction {
+    Left,
+    Right,
+}
+
+/// Available mini-games
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum MiniGameKind {
+    /// Guess which way the pet will turn
+    Guess,
+    /// Press as soon as the pet gives the signal
+    Reaction,
+}
+
+/// What the frontend should currently show
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum Phase {
+    /// Guess game: waiting for the player to pick left or right
+    Choose,
+    /// Guess game: the pet turned, showing whether the guess was right
+    Reveal { turned: Direction, won: bool },
+    /// Reaction game: waiting for the signal, don't press yet
+    Wait,
+    /// Reaction game: the signal is showing, press now
+    Go,
+    /// Reaction game: showing the outcome of the round
+    RoundOver { won: bool },
+    /// All rounds played
+    Finished(MiniGameResult),
+}
+
+/// Outcome of a finished mini-game
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct MiniGameResult {
+    /// Rounds won
+    pub wins: u8,
+    /// Rounds played
+    pub rounds: u8,
+}
+
+/// A running mini-game
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct MiniGame {
+    kind: MiniGameKind,
+    rng: Rng,
+    round: u8,
+    wins: u8,
+    phase: Phase,
+    /// When the current timed phase ends (or the signal appears)
+    deadline: u32,
+}
+
+impl MiniGame {
+    /// Start a new game
+    ///
+    /// `seed` should vary between games (e.g. taken from a clock) so the
+    /// pet doesn't turn the same way every time.
+    pub fn new(kind: MiniGameKind, seed: u32, now_ms: u32) -> Self {
+        let mut game = Self {
+            kind,
+            rng: Rng::new(seed),
+            round: 0,
+            wins: 0,
+            phase: Phase::Choose,
+            deadline: now_ms,
+        };
+        game.start_round(now_ms);
+        game
+    }
+
+    /// Which game this is
+    pub fn kind(&self) -> MiniGameKind {
+        self.kind
+    }
+
+    /// Current phase, for rendering
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    /// Current round (1-based)
+    pub fn round(&self) -> u8 {
+        (self.round + 1).min(ROUNDS)
+    }
+
+    /// Rounds won so far
+    pub fn wins(&self) -> u8 {
+        self.wins
+    }
+
+    /// Final result, once all rounds are played
+    pub fn result(&self) -> Option<MiniGameResult> {
+        match self.phase {
+            Phase::Finished(result) => Some(result),
+            _ => None,
+        }
+    }
+
+    /// Whether all rounds are played
+    pub fn is_finished(&self) -> bool {
+        self.result().is_some()
+    }
+
+    /// Handle a player input
+    pub fn input(&mut self, input: Input, now_ms: u32) {
+        self.update(now_ms);
+
+        match (self.phase, input) {
+            (Phase::Choose, Input::Left | Input::Right) => {
+                let choice = if input == Input::Left {
+                    Direction::Left
+                } else {
+                    Direction::Right
+                };
+                let turned = if self.rng.next() & 1 == 0 {
+                    Direction::Left
+                } else {
+                    Direction::Right
+                };
+                let won = choice == turned;
+                self.end_round(Phase::Reveal { turned, won }, won, now_ms);
+            }
+            (Phase::Wait, _) => {
+                // Pressed before the signal
+                self.end_round(Phase::RoundOver { won: false }, false, now_ms);
+            }
+            (Phase::Go, _) => {
+                let reaction = now_ms.wrapping_sub(self.deadline);
+                let won = (MIN_REACTION_MS..=REACTION_WINDOW_MS).contains(&reaction);
+                self.end_round(Phase::RoundOver { won }, won, now_ms);
+            }
+            _ => {}
+        }
+    }
+
+    /// Advance timed phases (call regularly, e.g. every frame)
+    pub fn update(&mut self, now_ms: u32) {
+        let reached = |deadline: u32| now_ms.wrapping_sub(deadline) < u32::MAX / 2;
+
+        match self.phase {
+            Phase::Wait if reached(self.deadline) => {
+                self.phase = Phase::Go;
+            }
+            Phase::Go if reached(self.deadline.wrapping_add(REACTION_WINDOW_MS + 1)) => {
+                // Too slow
+                self.end_round(Phase::RoundOver { won: false }, false, now_ms);
+            }
+            Phase::Reveal { .. } | Phase::RoundOver { .. } if reached(self.deadline) => {
+                self.round += 1;
+                if self.round >= ROUNDS {
+                    self.phase = Phase::Finished(MiniGameResult {
+                        wins: self.wins,
+                        rounds: ROUNDS,
+                    });
+                } else {
+                    self.start_round(now_ms);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn start_round(&mut self, now_ms: u32) {
+        match self.kind {
+            MiniGameKind::Guess => {
+                self.phase = Phase::Choose;
+            }
+            MiniGameKind::Reaction => {
+                let (min, max) = SIGNAL_DELAY_MS;
+                self.deadline = now_ms.wrapping_add(min + self.rng.next() % (max - min));
+                self.phase = Phase::Wait;
+            }
+        }
+    }
+
+    fn end_round(&mut self, phase: Phase, won: bool, now_ms: u32) {
+        if won {
+            self.wins += 1;
+        }
+        self.phase = phase;
+        self.deadline = now_ms.wrapping_add(REVEAL_MS);
+    }
+}
+
+/// Small xorshift generator, good enough for a toy
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+struct Rng(u32);
+
+impl Rng {
+    fn new(seed: u32) -> Self {
+        // xorshift gets stuck on zero
+        Self(if seed == 0 { 0x2545_f491 } else { seed })
+    }
+
+    fn next(&mut self) -> u32 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.0 = x;
+        x
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finish(game: &mut MiniGame, mut now: u32) -> MiniGameResult {
+        loop {
+            if let Some(result) = game.result() {
+                return result;
+            }
+            now += 50;
+            game.update(now);
+        }
+    }
+
+    #[test]
+    fn test_guess_game_plays_five_rounds() {
+        let mut game = MiniGame::new(MiniGameKind::Guess, 42, 0);
+        let mut now = 0;
+        for _ in 0..ROUNDS {
+            assert_eq!(game.phase(), Phase::Choose);
+            game.input(Input::Left, now);
+            assert!(matches!(game.phase(), Phase::Reveal { .. }));
+            now += REVEAL_MS;
+            game.update(now);
+        }
+        let result = game.result().unwrap();
+        assert_eq!(result.rounds, ROUNDS);
+        assert!(result.wins <= ROUNDS);
+    }
+
+    #[test]
+    fn test_reaction_win_and_false_start() {
+        let mut game = MiniGame::new(MiniGameKind::Reaction, 7, 0);
+        assert_eq!(game.phase(), Phase::Wait);
+
+        // Pressing early is a false start
+        game.input(Input::Press, 10);
+        assert_eq!(game.phase(), Phase::RoundOver { won: false });
+        game.update(10 + REVEAL_MS);
+        assert_eq!(game.phase(), Phase::Wait);
+
+        // Wait for the signal, then react in time
+        let mut now = 10 + REVEAL_MS;
+        while game.phase() == Phase::Wait {
+            now += 10;
+            game.update(now);
+        }
+        assert_eq!(game.phase(), Phase::Go);
+        game.input(Input::Press, now + 250);
+        assert_eq!(game.phase(), Phase::RoundOver { won: true });
+        assert_eq!(game.wins(), 1);
+
+        // Never pressing again loses the remaining rounds
+        let result = finish(&mut game, now + 250);
+        assert_eq!(result.wins, 1);
+    }
+}
@@ -0,0 +1,172 @@
+//! Mood model derived from the Tamagochi's stats
+//!
+//! Every mood has an entry threshold and a looser exit threshold, and a
+//! mood is kept for a minimum time unless something more urgent comes up.
+//! Together this keeps the displayed mood from flickering when a stat
+//! hovers around a boundary.
+
+use crate::{LifeStage, Tamagochi};
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+
+/// Minimum ticks a mood is shown before it may change to a less urgent one
+const MIN_MOOD_TICKS: u32 = 30;
+
+/// Pet-clock night: 22:00 to 06:00
+const NIGHT_START: u32 = 79_200;
+const NIGHT_END: u32 = 21_600;
+
+/// How the Tamagochi feels, ordered from most to least urgent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Mood {
+    /// Health is low
+    Sick,
+    /// Starving and neglected
+    Angry,
+    /// Hunger is high
+    Hungry,
+    /// Happiness is low
+    Sad,
+    /// It's night-time for the pet
+    Sleepy,
+    /// Could use some entertainment
+    Bored,
+    /// Everything is fine
+    Content,
+    /// Well fed, healthy and cheerful
+    Happy,
+}
+
+impl Mood {
+    /// All moods, from most to least urgent
+    pub const ALL: [Mood; 8] = [
+        Mood::Sick,
+        Mood::Angry,
+        Mood::Hungry,
+        Mood::Sad,
+        Mood::Sleepy,
+        Mood::Bored,
+        Mood::Content,
+        Mood::Happy,
+    ];
+
+    /// The mood the stats call for, given the mood currently shown
+    ///
+    /// Conditions for the current mood use looser thresholds, so the
+    /// stats must move clearly past a boundary to leave it.
+    pub fn from_stats(tama: &Tamagochi, current: Mood) -> Mood {
+        // Entry threshold, or the looser exit threshold for the current mood
+        let band = |mood: Mood, enter: u8, exit: u8| if current == mood { exit } else { enter };
+
+        if tama.health < band(Mood::Sick, 30, 40) {
+            return Mood::Sick;
+        }
+        if tama.hunger > band(Mood::Angry, 90, 80) && tama.happiness < band(Mood::Angry, 20, 30) {
+            return Mood::Angry;
+        }
+        if tama.hunger > band(Mood::Hungry, 80, 70) {
+            return Mood::Hungry;
+        }
+        if tama.happiness < band(Mood::Sad, 20, 30) {
+            return Mood::Sad;
+        }
+        if is_night(tama.age) && tama.stage != LifeStage::Egg {
+            return Mood::Sleepy;
+        }
+        if tama.happiness < band(Mood::Bored, 45, 50) {
+            return Mood::Bored;
+        }
+        if tama.happiness > band(Mood::Happy, 70, 60)
+            && tama.hunger < band(Mood::Happy, 50, 60)
+            && tama.health > band(Mood::Happy, 70, 60)
+        {
+            return Mood::Happy;
+        }
+        Mood::Content
+    }
+}
+
+/// Whether the pet clock (age modulo one day) is in the night
+fn is_night(age: u32) -> bool {
+    let time_of_day = age % 86_400;
+    !(NIGHT_END..NIGHT_START).contains(&time_of_day)
+}
+
+impl Tamagochi {
+    /// Re-evaluate the mood after the stats changed
+    ///
+    /// A more urgent mood takes over immediately; a calmer one only after
+    /// the current mood has been shown for a while.
+    pub(crate) fn update_mood(&mut self) {
+        let target = Mood::from_stats(self, self.mood);
+        if target == self.mood {
+            return;
+        }
+        let shown_for = self.age.saturating_sub(self.mood_since);
+        if target < self.mood || shown_for >= MIN_MOOD_TICKS {
+            self.mood = target;
+            self.mood_since = self.age;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn child() -> Tamagochi {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Child;
+        tama.age = 43_200; // noon
+        tama.mood = Mood::Content;
+        tama
+    }
+
+    #[test]
+    fn test_mood_from_stats() {
+        let mut tama = child();
+        tama.hunger = 85;
+        assert_eq!(Mood::from_stats(&tama, Mood::Content), Mood::Hungry);
+        tama.hunger = 95;
+        tama.happiness = 10;
+        assert_eq!(Mood::from_stats(&tama, Mood::Content), Mood::Angry);
+        tama.health = 10;
+        assert_eq!(Mood::from_stats(&tama, Mood::Content), Mood::Sick);
+    }
+
+    #[test]
+    fn test_hysteresis_keeps_mood() {
+        let mut tama = child();
+        tama.hunger = 75;
+        // Not hungry enough to become hungry...
+        assert_ne!(Mood::from_stats(&tama, Mood::Content), Mood::Hungry);
+        // ...but not full enough to stop being hungry either
+        assert_eq!(Mood::from_stats(&tama, Mood::Hungry), Mood::Hungry);
+    }
+
+    #[test]
+    fn test_calmer_mood_waits() {
+        let mut tama = child();
+        tama.mood = Mood::Hungry;
+        tama.mood_since = tama.age;
+        tama.hunger = 10;
+        tama.update_mood();
+        assert_eq!(tama.mood, Mood::Hungry);
+
+        tama.age += MIN_MOOD_TICKS;
+        tama.update_mood();
+        assert_ne!(tama.mood, Mood::Hungry);
+    }
+
+    #[test]
+    fn test_sleepy_at_night() {
+        let mut tama = child();
+        tama.age = 86_400 + 3_600; // 01:00 on day two
+        assert_eq!(Mood::from_stats(&tama, Mood::Content), Mood::Sleepy);
+    }
+}
@@ -0,0 +1,406 @@
+//! Shareable pet passcodes
+//!
+//! Like the password codes of the original toys, a passcode is a short
+//! string a player can write down or read out and type back in, on the
+//! desktop or with a device's buttons. It holds the stage (including the
+//! adult form), age and key stats, but not the exact incubation, mood or
+//! timing state, so an imported pet is a close copy rather than a clone.
+//!
+//! A code is 16 symbols of Crockford base32, shown in groups of four. A
+//! freshly laid egg is:
+//!
+//! ```text
+//! 4800-0S6B-4CGA-004S
+//! ```
+//!
+//! The first 15 symbols carry 75 bits, most significant bit first:
+//!
+//! | Bits | Content                                  |
+//! |------|------------------------------------------|
+//! | 3    | Passcode version                         |
+//! | 3    | Stage and adult form                     |
+//! | 1    | Alive                                    |
+//! | 17   | Age in minutes (saturating)              |
+//! | 7    | Hunger                                   |
+//! | 7    | Happiness                                |
+//! | 7    | Health                                   |
+//! | 7    | Care quality                             |
+//! | 7    | Weight                                   |
+//! | 6    | Care mistakes (saturating)               |
+//! | 8    | Generation (saturating)                  |
+//! | 2    | Zero padding                             |
+//!
+//! The last symbol is a Luhn mod 32 check digit, which catches every
+//! single mistyped symbol and most swapped neighbours. Decoding is
+//! forgiving: case, dashes and spaces are ignored, and `I`/`L` and `O`
+//! are read as `1` and `0`.
+
+use core::fmt;
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::save::{stage_from_tag, stage_tag};
+use crate::{Mood, Tamagochi};
+
+/// Current passcode version
+pub const PASSCODE_VERSION: u8 = 1;
+
+/// Number of symbols in a passcode, including the check digit
+pub const CODE_LEN: usize = 16;
+
+/// The 32 symbols a passcode is made of, in value order
+pub const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+const DATA_LEN: usize = CODE_LEN - 1;
+
+/// Errors when decoding a passcode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum PasscodeError {
+    /// The code doesn't have [`CODE_LEN`] symbols
+    BadLength,
+    /// The code contains a character outside the alphabet
+    BadCharacter(char),
+    /// The check digit doesn't match, the code was mistyped
+    BadCheckDigit,
+    /// The code was made by a newer (or unknown) passcode version
+    UnsupportedVersion(u8),
+    /// A field holds a value that can't occur in a real pet
+    InvalidField,
+}
+
+impl fmt::Display for PasscodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasscodeError::BadLength => write!(f, "passcode must have {} symbols", CODE_LEN),
+            PasscodeError::BadCharacter(c) => write!(f, "'{}' can't appear in a passcode", c),
+            PasscodeError::BadCheckDigit => write!(f, "passcode check digit mismatch, check for typos"),
+            PasscodeError::UnsupportedVersion(v) => write!(f, "unsupported passcode version {}", v),
+            PasscodeError::InvalidField => write!(f, "passcode contains an invalid field"),
+        }
+    }
+}
+
+/// An encoded passcode
+///
+/// Displays in groups of four symbols separated by dashes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Passcode {
+    symbols: [u8; CODE_LEN],
+}
+
+impl Passcode {
+    /// The symbols without separators
+    pub fn as_str(&self) -> &str {
+        // Every symbol comes from the ASCII alphabet
+        core::str::from_utf8(&self.symbols).unwrap_or("")
+    }
+}
+
+impl fmt::Display for Passcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, group) in self.as_str().as_bytes().chunks(4).enumerate() {
+            if i > 0 {
+                f.write_str("-")?;
+            }
+            // Groups are ASCII, so splitting by bytes is safe
+            f.write_str(core::str::from_utf8(group).unwrap_or(""))?;
+        }
+        Ok(())
+    }
+}
+
+/// Serialized as the dashed string players see
+#[cfg(feature = "serialization")]
+impl Serialize for Passcode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl<'de> Deserialize<'de> for Passcode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+
+        impl serde::de::Visitor<'_> for Visitor {
+            type Value = Passcode;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("a passcode")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, code: &str) -> Result<Passcode, E> {
+                decode(code).map(|tama| encode(&tama)).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
+/// Encode a Tamagochi as a passcode
+pub fn encode(tama: &Tamagochi) -> Passcode {
+    let mut bits = BitWriter::default();
+    bits.push(PASSCODE_VERSION as u128, 3);
+    bits.push(stage_tag(tama.stage) as u128, 3);
+    bits.push(tama.alive as u128, 1);
+    bits.push((tama.age / 60).min(0x1_ffff) as u128, 17);
+    bits.push(tama.hunger as u128, 7);
+    bits.push(tama.happiness as u128, 7);
+    bits.push(tama.health as u128, 7);
+    bits.push(tama.care_quality as u128, 7);
+    bits.push(tama.weight as u128, 7);
+    bits.push(tama.care_mistakes.min(63) as u128, 6);
+    bits.push(tama.generation.min(255) as u128, 8);
+    bits.push(0, 2);
+    debug_assert_eq!(bits.len, DATA_LEN * 5);
+
+    let mut values = [0; CODE_LEN];
+    for (i, value) in values[..DATA_LEN].iter_mut().enumerate() {
+        *value = (bits.value >> ((DATA_LEN - 1 - i) * 5)) as u8 & 0x1f;
+    }
+    values[DATA_LEN] = check_digit(&values[..DATA_LEN]);
+
+    Passcode {
+        symbols: values.map(|v| ALPHABET[v as usize]),
+    }
+}
+
+/// Decode a passcode typed by a player
+pub fn decode(code: &str) -> Result<Tamagochi, PasscodeError> {
+    let mut values = [0; CODE_LEN];
+    let mut len = 0;
+    for c in code.chars() {
+        if c == '-' || c.is_whitespace() {
+            continue;
+        }
+        let value = symbol_value(c).ok_or(PasscodeError::BadCharacter(c))?;
+        *values.get_mut(len).ok_or(PasscodeError::BadLength)? = value;
+        len += 1;
+    }
+    if len != CODE_LEN {
+        return Err(PasscodeError::BadLength);
+    }
+    if check_digit(&values[..DATA_LEN]) != values[DATA_LEN] {
+        return Err(PasscodeError::BadCheckDigit);
+    }
+
+    let mut bits = BitReader {
+        value: values[..DATA_LEN]
+            .iter()
+            .fold(0, |acc, &v| (acc << 5) | v as u128),
+        left: DATA_LEN * 5,
+    };
+    let version = bits.take(3) as u8;
+    if version != PASSCODE_VERSION {
+        return Err(PasscodeError::UnsupportedVersion(version));
+    }
+    let stage = stage_from_tag(bits.take(3) as u8).map_err(|_| PasscodeError::InvalidField)?;
+    let alive = bits.take(1) == 1;
+    let age = bits.take(17) as u32 * 60;
+    let hunger = stat(bits.take(7))?;
+    let happiness = stat(bits.take(7))?;
+    let health = stat(bits.take(7))?;
+    let care_quality = stat(bits.take(7))?;
+    let weight = bits.take(7) as u8;
+    let care_mistakes = bits.take(6) as u8;
+    let generation = bits.take(8) as u16;
+    if !(1..=99).contains(&weight) || generation == 0 || bits.take(2) != 0 {
+        return Err(PasscodeError::InvalidField);
+    }
+
+    let mut tama = Tamagochi {
+        hunger,
+        happiness,
+        health,
+        age,
+        stage,
+        alive,
+        care_quality,
+        weight,
+        care_mistakes,
+        mood_since: age,
+        generation,
+        ..Tamagochi::new()
+    };
+    tama.mood = Mood::from_stats(&tama, tama.mood);
+    Ok(tama)
+}
+
+impl Tamagochi {
+    /// Encode as a shareable passcode
+    pub fn to_passcode(&self) -> Passcode {
+        encode(self)
+    }
+
+    /// Decode from a passcode
+    pub fn from_passcode(code: &str) -> Result<Self, PasscodeError> {
+        decode(code)
+    }
+}
+
+/// Value of a typed symbol, accepting common look-alikes
+fn symbol_value(c: char) -> Option<u8> {
+    let c = match c.to_ascii_uppercase() {
+        'O' => '0',
+        'I' | 'L' => '1',
+        c => c,
+    };
+    ALPHABET
+        .iter()
+        .position(|&s| s as char == c)
+        .map(|v| v as u8)
+}
+
+/// Luhn mod 32 check digit over symbol values
+fn check_digit(values: &[u8]) -> u8 {
+    let sum: u32 = values
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &v)| {
+            let factor = if i % 2 == 0 { 2 } else { 1 };
+            let addend = v as u32 * factor;
+            addend / 32 + addend % 32
+        })
+        .sum();
+    ((32 - sum % 32) % 32) as u8
+}
+
+fn stat(value: u128) -> Result<u8, PasscodeError> {
+    if value <= 100 {
+        Ok(value as u8)
+    } else {
+        Err(PasscodeError::InvalidField)
+    }
+}
+
+#[derive(Default)]
+struct BitWriter {
+    value: u128,
+    len: usize,
+}
+
+impl BitWriter {
+    fn push(&mut self, value: u128, bits: usize) {
+        self.value = (self.value << bits) | (value & ((1 << bits) - 1));
+        self.len += bits;
+    }
+}
+
+struct BitReader {
+    value: u128,
+    left: usize,
+}
+
+impl BitReader {
+    fn take(&mut self, bits: usize) -> u128 {
+        self.left -= bits;
+        (self.value >> self.left) & ((1 << bits) - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AdultForm, LifeStage};
+
+    fn sample() -> Tamagochi {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Adult(AdultForm::Normal);
+        tama.age = 700_020;
+        tama.hunger = 35;
+        tama.happiness = 80;
+        tama.health = 97;
+        tama.care_quality = 64;
+        tama.weight = 21;
+        tama.care_mistakes = 4;
+        tama.generation = 2;
+        tama
+    }
+
+    #[test]
+    fn test_round_trip_keeps_key_stats() {
+        let code = sample().to_passcode();
+        let tama = Tamagochi::from_passcode(&code.to_string()).unwrap();
+        let original = sample();
+
+        assert_eq!(tama.stage, original.stage);
+        assert_eq!(tama.age, 700_020 / 60 * 60);
+        assert_eq!(tama.hunger, original.hunger);
+        assert_eq!(tama.happiness, original.happiness);
+        assert_eq!(tama.health, original.health);
+        assert_eq!(tama.care_quality, original.care_quality);
+        assert_eq!(tama.weight, original.weight);
+        assert_eq!(tama.care_mistakes, original.care_mistakes);
+        assert_eq!(tama.generation, original.generation);
+        assert!(tama.alive);
+        assert_eq!(tama.to_passcode(), code);
+    }
+
+    #[test]
+    fn test_format_and_forgiving_input() {
+        let code = sample().to_passcode();
+        let shown = code.to_string();
+        assert_eq!(shown.len(), CODE_LEN + 3);
+        assert_eq!(shown.matches('-').count(), 3);
+
+        let sloppy = code.as_str().to_ascii_lowercase().replace('0', "o").replace('1', "l");
+        assert_eq!(Tamagochi::from_passcode(&sloppy).unwrap().to_passcode(), code);
+    }
+
+    #[test]
+    fn test_catches_single_typos_and_swaps() {
+        let code = sample().to_passcode();
+        let symbols = code.as_str().as_bytes();
+
+        for i in 0..CODE_LEN {
+            for &other in ALPHABET.iter().filter(|&&s| s != symbols[i]) {
+                let mut typo = [0; CODE_LEN];
+                typo.copy_from_slice(symbols);
+                typo[i] = other;
+                let typo = core::str::from_utf8(&typo).unwrap();
+                assert_eq!(decode(typo).unwrap_err(), PasscodeError::BadCheckDigit, "{}", typo);
+            }
+        }
+
+        let mut swapped = [0; CODE_LEN];
+        swapped.copy_from_slice(symbols);
+        let i = (0..CODE_LEN - 1).find(|&i| swapped[i] != swapped[i + 1]).unwrap();
+        swapped.swap(i, i + 1);
+        assert!(decode(core::str::from_utf8(&swapped).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_codes() {
+        let code = sample().to_passcode().to_string();
+        assert_eq!(decode(&code[..10]).unwrap_err(), PasscodeError::BadLength);
+        assert_eq!(decode(&(code.clone() + "0")).unwrap_err(), PasscodeError::BadLength);
+        assert_eq!(decode(&code.replacen('-', "U", 1)).unwrap_err(), PasscodeError::BadCharacter('U'));
+    }
+
+    #[test]
+    fn test_rejects_unknown_version() {
+        // Version 7, everything else zero, with a valid check digit
+        let mut values = [0; CODE_LEN];
+        values[0] = 0b11100;
+        values[DATA_LEN] = check_digit(&values[..DATA_LEN]);
+        let code: [u8; CODE_LEN] = values.map(|v| ALPHABET[v as usize]);
+        assert_eq!(
+            decode(core::str::from_utf8(&code).unwrap()).unwrap_err(),
+            PasscodeError::UnsupportedVersion(7)
+        );
+    }
+
+    #[cfg(feature = "serialization")]
+    #[test]
+    fn test_serializes_as_dashed_string() {
+        let code = sample().to_passcode();
+        let json = serde_json::to_string(&code).unwrap();
+        assert_eq!(json, format!("\"{}\"", code));
+        assert_eq!(serde_json::from_str::<Passcode>(&json.to_lowercase()).unwrap(), code);
+        assert!(serde_json::from_str::<Passcode>("\"4800-0S6B-4CGA-004T\"").is_err());
+    }
+}
@@ -0,0 +1,437 @@
+//! Minimal QR code encoder for passcodes
+//!
+//! Produces version 1 (21×21) symbols with error correction level M in
+//! alphanumeric mode, which holds up to 20 characters – enough for a
+//! [`Passcode`] including its dashes. Small enough to fit the 128×64
+//! OLED at two pixels per module, and it needs neither `std` nor an
+//! allocator.
+
+use core::fmt;
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::passcode::Passcode;
+
+/// Width and height of the symbol in modules
+pub const QR_SIZE: usize = 21;
+
+/// Most characters a symbol can hold
+pub const MAX_CHARS: usize = 20;
+
+/// Light modules to leave around the symbol so scanners can find it
+pub const QUIET_ZONE: usize = 4;
+
+/// Characters of the QR alphanumeric mode, in value order
+const ALPHANUMERIC: &[u8; 45] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:";
+
+const DATA_CODEWORDS: usize = 16;
+const EC_CODEWORDS: usize = 10;
+
+/// Error correction level M in the format information
+const EC_LEVEL_M: u16 = 0b00;
+
+/// Errors when encoding text
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum QrError {
+    /// More than [`MAX_CHARS`] characters
+    TooLong,
+    /// A character outside the alphanumeric mode (digits, upper case
+    /// letters, space and `$%*+-./:`)
+    UnsupportedCharacter(char),
+}
+
+impl fmt::Display for QrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QrError::TooLong => write!(f, "text too long for a QR code (max {})", MAX_CHARS),
+            QrError::UnsupportedCharacter(c) => write!(f, "'{}' can't be encoded in a QR code", c),
+        }
+    }
+}
+
+/// A version 1 QR code symbol
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct QrCode {
+    /// One bit per module, bit `x` of row `y`, set for dark modules
+    rows: [u32; QR_SIZE],
+}
+
+impl QrCode {
+    /// Encode text in alphanumeric mode
+    pub fn encode(text: &str) -> Result<Self, QrError> {
+        let data = data_codewords(text)?;
+        let ec = reed_solomon(&data);
+
+        let mut codewords = [0; DATA_CODEWORDS + EC_CODEWORDS];
+        codewords[..DATA_CODEWORDS].copy_from_slice(&data);
+        codewords[DATA_CODEWORDS..].copy_from_slice(&ec);
+
+        let mut base = Self { rows: [0; QR_SIZE] };
+        base.place_data(&codewords);
+
+        // Pick the mask scanners will find easiest to read
+        let (mut best, mut best_penalty) = (base, u32::MAX);
+        for mask in 0..8 {
+            let mut candidate = base;
+            candidate.apply_mask(mask);
+            candidate.draw_function_patterns(mask);
+            let penalty = candidate.penalty();
+            if penalty < best_penalty {
+                best = candidate;
+                best_penalty = penalty;
+            }
+        }
+        Ok(best)
+    }
+
+    /// Width and height in modules
+    pub fn size(&self) -> usize {
+        QR_SIZE
+    }
+
+    /// Whether the module at column `x`, row `y` is dark
+    ///
+    /// Modules outside the symbol (the quiet zone) are light.
+    pub fn module(&self, x: usize, y: usize) -> bool {
+        x < QR_SIZE && y < QR_SIZE && self.rows[y] & (1 << x) != 0
+    }
+
+    fn set(&mut self, x: usize, y: usize, dark: bool) {
+        if dark {
+            self.rows[y] |= 1 << x;
+        } else {
+            self.rows[y] &= !(1 << x);
+        }
+    }
+
+    /// Fill the data area in the two-column zigzag from the bottom right
+    fn place_data(&mut self, codewords: &[u8]) {
+        let mut bit = 0;
+        let mut right = QR_SIZE - 1;
+        loop {
+            let upward = (right + 1) & 2 == 0;
+            for vert in 0..QR_SIZE {
+                let y = if upward { QR_SIZE - 1 - vert } else { vert };
+                for x in [right, right - 1] {
+                    if is_function(x, y) {
+                        continue;
+                    }
+                    let dark = codewords
+                        .get(bit / 8)
+                        .is_some_and(|byte| byte >> (7 - bit % 8) & 1 != 0);
+                    self.set(x, y, dark);
+                    bit += 1;
+                }
+            }
+            if right < 3 {
+                break;
+            }
+            right -= 2;
+            // Skip the vertical timing pattern
+            if right == 6 {
+                right = 5;
+            }
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u8) {
+        for y in 0..QR_SIZE {
+            for x in 0..QR_SIZE {
+                if !is_function(x, y) && mask_bit(mask, x, y) {
+                    self.rows[y] ^= 1 << x;
+                }
+            }
+        }
+    }
+
+    fn draw_function_patterns(&mut self, mask: u8) {
+        // Timing patterns
+        for i in 8..QR_SIZE - 8 {
+            self.set(6, i, i.is_multiple_of(2));
+            self.set(i, 6, i.is_multiple_of(2));
+        }
+
+        // Finder patterns with their light separators
+        for (cx, cy) in [(3, 3), (QR_SIZE - 4, 3), (3, QR_SIZE - 4)] {
+            for dy in -4i32..=4 {
+                for dx in -4i32..=4 {
+                    let (x, y) = (cx as i32 + dx, cy as i32 + dy);
+                    if (0..QR_SIZE as i32).contains(&x) && (0..QR_SIZE as i32).contains(&y) {
+                        let ring = dx.abs().max(dy.abs());
+                        self.set(x as usize, y as usize, ring != 2 && ring != 4);
+                    }
+                }
+            }
+        }
+
+        // Format information, twice
+        let format = format_bits(mask);
+        let bit = |i: usize| format >> i & 1 != 0;
+        for i in 0..6 {
+            self.set(8, i, bit(i));
+        }
+        self.set(8, 7, bit(6));
+        self.set(8, 8, bit(7));
+        self.set(7, 8, bit(8));
+        for i in 9..15 {
+            self.set(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set(QR_SIZE - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set(8, QR_SIZE - 15 + i, bit(i));
+        }
+        // Always dark
+        self.set(8, QR_SIZE - 8, true);
+    }
+
+    /// Mask evaluation score from the specification, lower is better
+    fn penalty(&self) -> u32 {
+        let mut penalty = 0;
+
+        for horizontal in [true, false] {
+            for line in 0..QR_SIZE {
+                let at = |i: usize| {
+                    if horizontal {
+                        self.module(i, line)
+                    } else {
+                        self.module(line, i)
+                    }
+                };
+
+                // Runs of five or more same-coloured modules
+                let mut run = 1;
+                for i in 1..QR_SIZE {
+                    if at(i) == at(i - 1) {
+                        run += 1;
+                        if run == 5 {
+                            penalty += 3;
+                        } else if run > 5 {
+                            penalty += 1;
+                        }
+                    } else {
+                        run = 1;
+                    }
+                }
+
+                // Patterns that look like a finder
+                const FINDER: [bool; 11] = [
+                    true, false, true, true, true, false, true, false, false, false, false,
+                ];
+                for start in 0..=QR_SIZE - FINDER.len() {
+                    let forward = (0..FINDER.len()).all(|i| at(start + i) == FINDER[i]);
+                    let backward = (0..FINDER.len()).all(|i| at(start + i) == FINDER[FINDER.len() - 1 - i]);
+                    if forward || backward {
+                        penalty += 40;
+                    }
+                }
+            }
+        }
+
+        // 2×2 blocks of one colour
+        for y in 0..QR_SIZE - 1 {
+            for x in 0..QR_SIZE - 1 {
+                let colour = self.module(x, y);
+                if self.module(x + 1, y) == colour
+                    && self.module(x, y + 1) == colour
+                    && self.module(x + 1, y + 1) == colour
+                {
+                    penalty += 3;
+                }
+            }
+        }
+
+        // Balance of dark and light modules
+        let dark: u32 = self.rows.iter().map(|row| row.count_ones()).sum();
+        let total = (QR_SIZE * QR_SIZE) as u32;
+        let deviation = (dark * 20).abs_diff(total * 10);
+        penalty += deviation.div_ceil(total).saturating_sub(1) * 10;
+
+        penalty
+    }
+}
+
+impl Passcode {
+    /// The passcode, with dashes, as a QR code
+    pub fn to_qr(&self) -> QrCode {
+        let mut text = [0; CODE_TEXT_LEN];
+        for (i, group) in self.as_str().as_bytes().chunks(4).enumerate() {
+            let start = i * 5;
+            text[start..start + 4].copy_from_slice(group);
+            if start + 4 < CODE_TEXT_LEN {
+                text[start + 4] = b'-';
+            }
+        }
+        // The passcode alphabet is a subset of the alphanumeric mode
+        let text = core::str::from_utf8(&text).unwrap_or("");
+        QrCode::encode(text).unwrap_or(QrCode { rows: [0; QR_SIZE] })
+    }
+}
+
+/// Length of a passcode shown with dashes
+const CODE_TEXT_LEN: usize = crate::passcode::CODE_LEN / 4 * 5 - 1;
+
+/// Whether a module belongs to a function pattern rather than data
+fn is_function(x: usize, y: usize) -> bool {
+    let near = |v: usize| v <= 8;
+    let far = |v: usize| v >= QR_SIZE - 8;
+    x == 6 || y == 6 || (near(x) && near(y)) || (far(x) && near(y)) || (near(x) && far(y))
+}
+
+fn mask_bit(mask: u8, x: usize, y: usize) -> bool {
+    match mask {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (x / 3 + y / 2).is_multiple_of(2),
+        5 => (x * y % 2 + x * y % 3) == 0,
+        6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+    }
+}
+
+/// 15 format bits: level and mask, BCH protected and XOR masked
+fn format_bits(mask: u8) -> u16 {
+    let data = (EC_LEVEL_M << 3) | mask as u16;
+    let mut rem = data;
+    for _ in 0..10 {
+        rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+    }
+    ((data << 10) | (rem & 0x3ff)) ^ 0x5412
+}
+
+/// Mode indicator, length, characters, terminator and padding
+fn data_codewords(text: &str) -> Result<[u8; DATA_CODEWORDS], QrError> {
+    if text.len() > MAX_CHARS {
+        return Err(QrError::TooLong);
+    }
+    let mut values = [0u16; MAX_CHARS];
+    for (value, c) in values.iter_mut().zip(text.chars()) {
+        *value = ALPHANUMERIC
+            .iter()
+            .position(|&a| a as char == c)
+            .ok_or(QrError::UnsupportedCharacter(c))? as u16;
+    }
+    let values = &values[..text.len()];
+
+    let mut bits = BitBuffer {
+        bytes: [0; DATA_CODEWORDS],
+        len: 0,
+    };
+    bits.push(0b0010, 4);
+    bits.push(values.len() as u16, 9);
+    for pair in values.chunks(2) {
+        match *pair {
+            [a, b] => bits.push(a * 45 + b, 11),
+            [a] => bits.push(a, 6),
+            _ => {}
+        }
+    }
+    let terminator = (DATA_CODEWORDS * 8 - bits.len).min(4);
+    bits.push(0, terminator);
+
+    let used = bits.len.div_ceil(8);
+    for (i, byte) in bits.bytes[used..].iter_mut().enumerate() {
+        *byte = if i.is_multiple_of(2) { 0xec } else { 0x11 };
+    }
+    Ok(bits.bytes)
+}
+
+struct BitBuffer {
+    bytes: [u8; DATA_CODEWORDS],
+    len: usize,
+}
+
+impl BitBuffer {
+    fn push(&mut self, value: u16, bits: usize) {
+        for i in (0..bits).rev() {
+            if value >> i & 1 != 0 {
+                self.bytes[self.len / 8] |= 0x80 >> (self.len % 8);
+            }
+            self.len += 1;
+        }
+    }
+}
+
+/// Error correction codewords over GF(256)
+fn reed_solomon(data: &[u8; DATA_CODEWORDS]) -> [u8; EC_CODEWORDS] {
+    // Generator polynomial (x - 2^0)(x - 2^1)…, leading 1 omitted
+    let mut divisor = [0; EC_CODEWORDS];
+    divisor[EC_CODEWORDS - 1] = 1;
+    let mut root = 1;
+    for _ in 0..EC_CODEWORDS {
+        for j in 0..EC_CODEWORDS {
+            divisor[j] = gf_mul(divisor[j], root);
+            if j + 1 < EC_CODEWORDS {
+                divisor[j] ^= divisor[j + 1];
+            }
+        }
+        root = gf_mul(root, 2);
+    }
+
+    let mut remainder = [0; EC_CODEWORDS];
+    for &byte in data {
+        let factor = byte ^ remainder[0];
+        remainder.copy_within(1.., 0);
+        remainder[EC_CODEWORDS - 1] = 0;
+        for (r, &d) in remainder.iter_mut().zip(&divisor) {
+            *r ^= gf_mul(d, factor);
+        }
+    }
+    remainder
+}
+
+fn gf_mul(x: u8, y: u8) -> u8 {
+    let mut z: u16 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11d);
+        z ^= ((y as u16 >> i) & 1) * x as u16;
+    }
+    z as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_bits_match_specification() {
+        // Level M, mask 0 from the format information table
+        assert_eq!(format_bits(0), 0b101_0100_0001_0010);
+    }
+
+    #[test]
+    fn test_codewords_match_reference() {
+        // HELLO WORLD at 1-M, as worked through in common QR tutorials
+        let data = data_codewords("HELLO WORLD").unwrap();
+        assert_eq!(
+            data,
+            [32, 91, 11, 120, 209, 114, 220, 77, 67, 64, 236, 17, 236, 17, 236, 17]
+        );
+        assert_eq!(
+            reed_solomon(&data),
+            [196, 35, 39, 119, 235, 215, 231, 226, 93, 23]
+        );
+    }
+
+    #[test]
+    fn test_rejects_unsupported_text() {
+        assert_eq!(QrCode::encode("lower"), Err(QrError::UnsupportedCharacter('l')));
+        assert_eq!(QrCode::encode("0123456789ABCDEFGHIJK"), Err(QrError::TooLong));
+    }
+
+    #[test]
+    fn test_finder_patterns_in_corners() {
+        let qr = QrCode::encode("HELLO").unwrap();
+        for (x, y) in [(0, 0), (QR_SIZE - 7, 0), (0, QR_SIZE - 7)] {
+            assert!(qr.module(x, y) && qr.module(x + 3, y + 3) && qr.module(x + 6, y + 6));
+            assert!(!qr.module(x + 1, y + 1) && !qr.module(x + 5, y + 5));
+        }
+        assert!(!qr.module(QR_SIZE, 0));
+    }
+}
@@ -0,0 +1,182 @@
+//! Stage-dependent game rules
+//!
+//! Stat decay rates and action effects for every life stage. Frontends
+//! can query these tables to show which actions are available.
+
+use crate::LifeStage;
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+
+/// Player actions that can be performed on the Tamagochi
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub enum Action {
+    /// Feed a meal
+    Feed,
+    /// Play together
+    Play,
+    /// Give medicine
+    Medicine,
+    /// Clean up
+    Clean,
+    /// Keep the egg warm during incubation
+    Warm,
+}
+
+impl Action {
+    /// All actions
+    pub const ALL: [Action; 5] = [
+        Action::Feed,
+        Action::Play,
+        Action::Medicine,
+        Action::Clean,
+        Action::Warm,
+    ];
+
+    /// The actions mapped to the four hardware buttons, in button order
+    pub const BUTTONS: [Action; 4] = [Action::Feed, Action::Play, Action::Medicine, Action::Clean];
+}
+
+/// Natural stat decay for a life stage
+///
+/// Rates are given in points per 10 ticks so that stages can decay
+/// slower than one point per tick without extra state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct DecayRates {
+    /// Hunger gained per 10 ticks
+    pub hunger: u8,
+    /// Happiness lost per 10 ticks
+    pub happiness: u8,
+}
+
+impl DecayRates {
+    /// Hunger gained on the tick that brings the age to `age`
+    pub fn hunger_step(&self, age: u32) -> u8 {
+        step(self.hunger, age)
+    }
+
+    /// Happiness lost on the tick that brings the age to `age`
+    pub fn happiness_step(&self, age: u32) -> u8 {
+        step(self.happiness, age)
+    }
+}
+
+/// Spread `rate` points per 10 ticks evenly over the ticks
+pub(crate) fn step(rate: u8, age: u32) -> u8 {
+    if age == 0 {
+        return 0;
+    }
+    let rate = rate as u64;
+    let age = age as u64;
+    (rate * age / 10 - rate * (age - 1) / 10) as u8
+}
+
+/// Stat changes applied by an action
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct ActionEffect {
+    /// Change in hunger (negative = less hungry)
+    pub hunger: i8,
+    /// Change in happiness
+    pub happiness: i8,
+    /// Change in health
+    pub health: i8,
+    /// Change in care quality
+    pub care: i8,
+    /// Change in weight
+    pub weight: i8,
+}
+
+impl ActionEffect {
+    const fn new(hunger: i8, happiness: i8, health: i8, care: i8) -> Self {
+        Self {
+            hunger,
+            happiness,
+            health,
+            care,
+            weight: 0,
+        }
+    }
+
+    const fn with_weight(self, weight: i8) -> Self {
+        Self { weight, ..self }
+    }
+}
+
+impl LifeStage {
+    /// Natural stat decay for this stage
+    ///
+    /// Eggs don't get hungry, babies get hungry fast, teenagers get
+    /// bored fast and elders have a small appetite.
+    pub fn decay_rates(&self) -> DecayRates {
+        let (hunger, happiness) = match self {
+            LifeStage::Egg => (0, 0),
+            LifeStage::Baby => (20, 10),
+            LifeStage::Child => (10, 10),
+            LifeStage::Teenager => (10, 20),
+            LifeStage::Adult(_) => (10, 10),
+            LifeStage::Elder => (5, 10),
+        };
+        DecayRates { hunger, happiness }
+    }
+
+    /// Effect of an action in this stage, or `None` if unavailable
+    pub fn action_effect(&self, action: Action) -> Option<ActionEffect> {
+        let effect = match (self, action) {
+            // An unhatched egg can only be kept warm
+            (LifeStage::Egg, Action::Warm) => ActionEffect::new(0, 0, 0, 1),
+            (LifeStage::Egg, _) | (_, Action::Warm) => return None,
+
+            // Babies eat small meals and tire quickly when playing
+            (LifeStage::Baby, Action::Feed) => ActionEffect::new(-15, 5, 0, 2).with_weight(1),
+            (LifeStage::Baby, Action::Play) => ActionEffect::new(8, 10, 0, 3),
+
+            // Teenagers enjoy playing the most
+            (LifeStage::Teenager, Action::Play) => ActionEffect::new(5, 20, 0, 3),
+
+            // Elders recover more slowly
+            (LifeStage::Elder, Action::Feed) => ActionEffect::new(-10, 2, 0, 2).with_weight(1),
+            (LifeStage::Elder, Action::Play) => ActionEffect::new(5, 7, 0, 3),
+            (LifeStage::Elder, Action::Medicine) => ActionEffect::new(0, -5, 15, 1),
+            (LifeStage::Elder, Action::Clean) => ActionEffect::new(0, 5, 0, 2),
+
+            (_, Action::Feed) => ActionEffect::new(-20, 5, 0, 2).with_weight(1),
+            (_, Action::Play) => ActionEffect::new(5, 15, 0, 3).with_weight(-1),
+            (_, Action::Medicine) => ActionEffect::new(0, -5, 30, 1),
+            (_, Action::Clean) => ActionEffect::new(0, 10, 0, 2),
+        };
+        Some(effect)
+    }
+
+    /// Whether an action can be performed in this stage
+    pub fn allows(&self, action: Action) -> bool {
+        self.action_effect(action).is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decay_spreads_evenly() {
+        let rates = LifeStage::Elder.decay_rates();
+        let total: u32 = (1..=100).map(|age| rates.hunger_step(age) as u32).sum();
+        assert_eq!(total, 50);
+    }
+
+    #[test]
+    fn test_egg_only_allows_warming() {
+        for action in Action::BUTTONS {
+            assert!(!LifeStage::Egg.allows(action));
+            assert!(LifeStage::Child.allows(action));
+        }
+        assert!(LifeStage::Egg.allows(Action::Warm));
+        assert!(!LifeStage::Child.allows(Action::Warm));
+    }
+}
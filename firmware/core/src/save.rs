@@ -0,0 +1,402 @@
+//! Compact binary save format
+//!
+//! A fixed-size, allocation-free encoding of [`Tamagochi`] for targets
+//! without `std`, such as the ESP32 flash. Layout (little endian):
+//!
+//! | Offset | Size | Content                              |
+//! |--------|------|--------------------------------------|
+//! | 0      | 4    | Magic `TAMA`                         |
+//! | 4      | 1    | Format version                       |
+//! | 5      | 1    | Payload length                       |
+//! | 6      | n    | Payload (the pet's fields)           |
+//! | 6 + n  | 4    | CRC32 (IEEE) of everything before it |
+//!
+//! The format version follows the [schema version](crate::schema); older
+//! saves are decoded with their frozen layout and migrated.
+
+use core::fmt;
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{SaveV1, SCHEMA_VERSION};
+use crate::{AdultForm, LifeStage, Mood, Tamagochi};
+
+/// Magic number at the start of every save
+pub const MAGIC: [u8; 4] = *b"TAMA";
+
+/// Current format version
+pub const FORMAT_VERSION: u8 = SCHEMA_VERSION;
+
+const HEADER_SIZE: usize = 6;
+const PAYLOAD_SIZE: usize = 26;
+const PAYLOAD_SIZE_V1: usize = 24;
+const CRC_SIZE: usize = 4;
+
+/// Size of an encoded save in bytes
+pub const SAVE_SIZE: usize = HEADER_SIZE + PAYLOAD_SIZE + CRC_SIZE;
+
+/// Errors when encoding or decoding a save
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum SaveError {
+    /// The output buffer is smaller than [`SAVE_SIZE`]
+    BufferTooSmall,
+    /// The input ends before the save does
+    Truncated,
+    /// The input doesn't start with [`MAGIC`]
+    BadMagic,
+    /// The save was written by a newer (or unknown) format version
+    UnsupportedVersion(u8),
+    /// The payload length doesn't match the format version
+    BadLength,
+    /// The checksum doesn't match, the save is corrupt
+    BadChecksum,
+    /// A field holds a value that can't occur in a valid save
+    InvalidField,
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::BufferTooSmall => write!(f, "buffer too small for save"),
+            SaveError::Truncated => write!(f, "save is truncated"),
+            SaveError::BadMagic => write!(f, "not a Tamagochi save"),
+            SaveError::UnsupportedVersion(v) => write!(f, "unsupported save format version {}", v),
+            SaveError::BadLength => write!(f, "save payload has the wrong length"),
+            SaveError::BadChecksum => write!(f, "save checksum mismatch"),
+            SaveError::InvalidField => write!(f, "save contains an invalid field"),
+        }
+    }
+}
+
+/// Encode a Tamagochi into `buf`, returning the number of bytes written
+pub fn encode(tama: &Tamagochi, buf: &mut [u8]) -> Result<usize, SaveError> {
+    let buf = buf.get_mut(..SAVE_SIZE).ok_or(SaveError::BufferTooSmall)?;
+
+    buf[..4].copy_from_slice(&MAGIC);
+    buf[4] = FORMAT_VERSION;
+    buf[5] = PAYLOAD_SIZE as u8;
+
+    let mut w = Writer {
+        buf: &mut buf[HEADER_SIZE..HEADER_SIZE + PAYLOAD_SIZE],
+        pos: 0,
+    };
+    w.u8(tama.hunger);
+    w.u8(tama.happiness);
+    w.u8(tama.health);
+    w.u32(tama.age);
+    w.u8(stage_tag(tama.stage));
+    w.u8(tama.alive as u8);
+    w.u8(tama.care_quality);
+    w.u8(tama.weight);
+    w.u8(tama.care_mistakes);
+    w.u32(tama.adult_duration);
+    w.u8(tama.warmth);
+    w.u16(tama.incubation);
+    w.u8(mood_tag(tama.mood));
+    w.u32(tama.mood_since);
+    w.u16(tama.generation);
+    debug_assert_eq!(w.pos, PAYLOAD_SIZE);
+
+    let crc = crc32(&buf[..HEADER_SIZE + PAYLOAD_SIZE]);
+    buf[HEADER_SIZE + PAYLOAD_SIZE..].copy_from_slice(&crc.to_le_bytes());
+    Ok(SAVE_SIZE)
+}
+
+/// Decode a Tamagochi, rejecting truncated, corrupt or invalid saves
+///
+/// Saves from older format versions are migrated to the current schema.
+/// Bytes after the end of the save are ignored.
+pub fn decode(bytes: &[u8]) -> Result<Tamagochi, SaveError> {
+    if bytes.len() < HEADER_SIZE {
+        return Err(SaveError::Truncated);
+    }
+    if bytes[..4] != MAGIC {
+        return Err(SaveError::BadMagic);
+    }
+    let version = bytes[4];
+    let payload_size = match version {
+        1 => PAYLOAD_SIZE_V1,
+        FORMAT_VERSION => PAYLOAD_SIZE,
+        _ => return Err(SaveError::UnsupportedVersion(version)),
+    };
+    if bytes[5] as usize != payload_size {
+        return Err(SaveError::BadLength);
+    }
+    let bytes = bytes
+        .get(..HEADER_SIZE + payload_size + CRC_SIZE)
+        .ok_or(SaveError::Truncated)?;
+
+    let (data, crc) = bytes.split_at(HEADER_SIZE + payload_size);
+    if crc32(data) != u32::from_le_bytes([crc[0], crc[1], crc[2], crc[3]]) {
+        return Err(SaveError::BadChecksum);
+    }
+
+    let mut r = Reader {
+        buf: &data[HEADER_SIZE..],
+        pos: 0,
+    };
+    let tama = match version {
+        1 => read_v1(&mut r)?.migrate(),
+        _ => read_v2(&mut r)?,
+    };
+    if !(1..=99).contains(&tama.weight) {
+        return Err(SaveError::InvalidField);
+    }
+    Ok(tama)
+}
+
+/// Frozen payload layout of format version 1
+fn read_v1(r: &mut Reader) -> Result<SaveV1, SaveError> {
+    Ok(SaveV1 {
+        hunger: r.stat()?,
+        happiness: r.stat()?,
+        health: r.stat()?,
+        age: r.u32(),
+        stage: stage_from_tag(r.u8())?,
+        alive: r.bool()?,
+        care_quality: r.stat()?,
+        weight: r.u8(),
+        care_mistakes: r.u8(),
+        adult_duration: r.u32(),
+        warmth: r.stat()?,
+        incubation: r.u16(),
+        mood: mood_from_tag(r.u8())?,
+        mood_since: r.u32(),
+    })
+}
+
+/// Payload layout of format version 2 (current)
+fn read_v2(r: &mut Reader) -> Result<Tamagochi, SaveError> {
+    Ok(Tamagochi {
+        hunger: r.stat()?,
+        happiness: r.stat()?,
+        health: r.stat()?,
+        age: r.u32(),
+        stage: stage_from_tag(r.u8())?,
+        alive: r.bool()?,
+        care_quality: r.stat()?,
+        weight: r.u8(),
+        care_mistakes: r.u8(),
+        adult_duration: r.u32(),
+        warmth: r.stat()?,
+        incubation: r.u16(),
+        mood: mood_from_tag(r.u8())?,
+        mood_since: r.u32(),
+        generation: r.u16(),
+    })
+}
+
+impl Tamagochi {
+    /// Encode into the binary save format
+    pub fn to_bytes(&self) -> [u8; SAVE_SIZE] {
+        let mut buf = [0; SAVE_SIZE];
+        // The buffer has exactly the right size
+        let _ = encode(self, &mut buf);
+        buf
+    }
+
+    /// Decode from the binary save format
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SaveError> {
+        decode(bytes)
+    }
+}
+
+pub(crate) fn stage_tag(stage: LifeStage) -> u8 {
+    match stage {
+        LifeStage::Egg => 0,
+        LifeStage::Baby => 1,
+        LifeStage::Child => 2,
+        LifeStage::Teenager => 3,
+        LifeStage::Adult(AdultForm::Neglected) => 4,
+        LifeStage::Adult(AdultForm::Normal) => 5,
+        LifeStage::Adult(AdultForm::Premium) => 6,
+        LifeStage::Elder => 7,
+    }
+}
+
+pub(crate) fn stage_from_tag(tag: u8) -> Result<LifeStage, SaveError> {
+    Ok(match tag {
+        0 => LifeStage::Egg,
+        1 => LifeStage::Baby,
+        2 => LifeStage::Child,
+        3 => LifeStage::Teenager,
+        4 => LifeStage::Adult(AdultForm::Neglected),
+        5 => LifeStage::Adult(AdultForm::Normal),
+        6 => LifeStage::Adult(AdultForm::Premium),
+        7 => LifeStage::Elder,
+        _ => return Err(SaveError::InvalidField),
+    })
+}
+
+fn mood_tag(mood: Mood) -> u8 {
+    Mood::ALL.iter().position(|m| *m == mood).unwrap_or(0) as u8
+}
+
+fn mood_from_tag(tag: u8) -> Result<Mood, SaveError> {
+    Mood::ALL
+        .get(tag as usize)
+        .copied()
+        .ok_or(SaveError::InvalidField)
+}
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl Writer<'_> {
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes(&[value]);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+}
+
+/// Reads from a payload whose length was already checked
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let mut out = [0; N];
+        out.copy_from_slice(&self.buf[self.pos..self.pos + N]);
+        self.pos += N;
+        out
+    }
+
+    fn u8(&mut self) -> u8 {
+        self.take::<1>()[0]
+    }
+
+    fn u16(&mut self) -> u16 {
+        u16::from_le_bytes(self.take())
+    }
+
+    fn u32(&mut self) -> u32 {
+        u32::from_le_bytes(self.take())
+    }
+
+    /// A 0-100 stat
+    fn stat(&mut self) -> Result<u8, SaveError> {
+        match self.u8() {
+            value @ 0..=100 => Ok(value),
+            _ => Err(SaveError::InvalidField),
+        }
+    }
+
+    fn bool(&mut self) -> Result<bool, SaveError> {
+        match self.u8() {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(SaveError::InvalidField),
+        }
+    }
+}
+
+/// CRC32 (IEEE 802.3, as used by zip and PNG)
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc = CRC_TABLE[((crc ^ byte as u32) & 0xff) as usize] ^ (crc >> 8);
+    }
+    !crc
+}
+
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Tamagochi {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Adult(AdultForm::Premium);
+        tama.age = 700_000;
+        tama.hunger = 12;
+        tama.mood = Mood::Sleepy;
+        tama.incubation = 2400;
+        tama.generation = 3;
+        tama
+    }
+
+    #[test]
+    fn test_crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn test_round_trip_is_byte_identical() {
+        let bytes = sample().to_bytes();
+        let decoded = Tamagochi::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.stage, LifeStage::Adult(AdultForm::Premium));
+        assert_eq!(decoded.age, 700_000);
+        assert_eq!(decoded.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_rejects_truncated() {
+        let bytes = sample().to_bytes();
+        for len in 0..SAVE_SIZE {
+            assert!(Tamagochi::from_bytes(&bytes[..len]).is_err(), "length {}", len);
+        }
+    }
+
+    #[test]
+    fn test_rejects_corruption() {
+        let bytes = sample().to_bytes();
+        for i in 0..SAVE_SIZE {
+            let mut corrupt = bytes;
+            corrupt[i] ^= 0x10;
+            assert!(Tamagochi::from_bytes(&corrupt).is_err(), "byte {}", i);
+        }
+    }
+
+    #[test]
+    fn test_rejects_newer_version() {
+        let mut bytes = sample().to_bytes();
+        bytes[4] = FORMAT_VERSION + 1;
+        assert_eq!(
+            Tamagochi::from_bytes(&bytes).unwrap_err(),
+            SaveError::UnsupportedVersion(FORMAT_VERSION + 1)
+        );
+    }
+
+    #[test]
+    fn test_encode_needs_room() {
+        let mut buf = [0; SAVE_SIZE - 1];
+        assert_eq!(encode(&sample(), &mut buf), Err(SaveError::BufferTooSmall));
+    }
+}
@@ -0,0 +1,262 @@
+//! Versioned save schema and migrations
+//!
+//! Every time a field is added to [`Tamagochi`], the save schema version
+//! goes up and the previous layout is frozen here together with a
+//! migration to the next version. Old saves are migrated step by step
+//! (v1 → v2 → …) on load; saves from a newer version are refused.
+//!
+//! | Version | Change                        |
+//! |---------|-------------------------------|
+//! | 1       | The bare pet, without envelope |
+//! | 2       | Added `generation`            |
+//!
+//! JSON saves of the first release only hold the pet's first seven
+//! fields; the fields added to version 1 after it (`weight` to
+//! `mood_since`) take the values of a new pet when missing.
+//!
+//! When adding version 3: freeze the current layout as `SaveV2`, give it
+//! a `migrate` into the new [`Tamagochi`], let `SaveV1::migrate` go
+//! through it, and bump [`SCHEMA_VERSION`] and the binary format.
+
+#[cfg(feature = "serialization")]
+use alloc::string::String;
+#[cfg(feature = "serialization")]
+use core::fmt;
+#[cfg(feature = "serialization")]
+use serde::de::{self, DeserializeSeed, IgnoredAny, IntoDeserializer, MapAccess, Visitor};
+#[cfg(feature = "serialization")]
+use serde::de::value::MapAccessDeserializer;
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Deserializer, Serialize};
+#[cfg(feature = "json-schema")]
+use schemars::JsonSchema;
+
+#[cfg(feature = "serialization")]
+use crate::save::SaveError;
+use crate::{LifeStage, Mood, Tamagochi};
+
+/// Current save schema version (shared by the JSON and binary formats)
+pub const SCHEMA_VERSION: u8 = 2;
+
+/// Save layout of schema version 1 (before generations)
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub struct SaveV1 {
+    pub hunger: u8,
+    pub happiness: u8,
+    pub health: u8,
+    pub age: u32,
+    pub stage: LifeStage,
+    pub alive: bool,
+    pub care_quality: u8,
+    #[cfg_attr(feature = "serialization", serde(default = "fresh::weight"))]
+    pub weight: u8,
+    #[cfg_attr(feature = "serialization", serde(default = "fresh::care_mistakes"))]
+    pub care_mistakes: u8,
+    #[cfg_attr(feature = "serialization", serde(default = "fresh::adult_duration"))]
+    pub adult_duration: u32,
+    #[cfg_attr(feature = "serialization", serde(default = "fresh::warmth"))]
+    pub warmth: u8,
+    #[cfg_attr(feature = "serialization", serde(default = "fresh::incubation"))]
+    pub incubation: u16,
+    #[cfg_attr(feature = "serialization", serde(default = "fresh::mood"))]
+    pub mood: Mood,
+    #[cfg_attr(feature = "serialization", serde(default = "fresh::mood_since"))]
+    pub mood_since: u32,
+}
+
+/// Values of a new pet, for v1 fields missing from first-release saves
+#[cfg(feature = "serialization")]
+mod fresh {
+    use crate::{Mood, Tamagochi};
+
+    pub fn weight() -> u8 {
+        Tamagochi::new().weight
+    }
+
+    pub fn care_mistakes() -> u8 {
+        Tamagochi::new().care_mistakes
+    }
+
+    pub fn adult_duration() -> u32 {
+        Tamagochi::new().adult_duration
+    }
+
+    pub fn warmth() -> u8 {
+        Tamagochi::new().warmth
+    }
+
+    pub fn incubation() -> u16 {
+        Tamagochi::new().incubation
+    }
+
+    pub fn mood() -> Mood {
+        Tamagochi::new().mood
+    }
+
+    pub fn mood_since() -> u32 {
+        Tamagochi::new().mood_since
+    }
+}
+
+impl SaveV1 {
+    /// Migrate to the current schema
+    ///
+    /// v1 → v2: pets saved before generations were tracked are the first
+    /// of their line.
+    pub fn migrate(self) -> Tamagochi {
+        Tamagochi {
+            hunger: self.hunger,
+            happiness: self.happiness,
+            health: self.health,
+            age: self.age,
+            stage: self.stage,
+            alive: self.alive,
+            care_quality: self.care_quality,
+            weight: self.weight,
+            care_mistakes: self.care_mistakes,
+            adult_duration: self.adult_duration,
+            warmth: self.warmth,
+            incubation: self.incubation,
+            mood: self.mood,
+            mood_since: self.mood_since,
+            generation: 1,
+        }
+    }
+}
+
+/// A JSON save file as written by the current version
+///
+/// Version 1 JSON saves were the bare serialized pet without an envelope.
+#[cfg(feature = "serialization")]
+#[derive(Clone, Copy, Debug, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(JsonSchema))]
+pub struct SaveFile<'a> {
+    /// Schema version, always [`SCHEMA_VERSION`] when writing
+    pub version: u8,
+    /// The pet
+    pub pet: &'a Tamagochi,
+}
+
+#[cfg(feature = "serialization")]
+impl<'a> SaveFile<'a> {
+    /// Wrap a pet for saving in the current schema
+    pub fn new(pet: &'a Tamagochi) -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            pet,
+        }
+    }
+}
+
+/// A JSON save file of any known or future version
+///
+/// Deserialize into this, then call [`AnySave::migrate`] to get the pet
+/// in the current schema. The `version` is read before the pet, so a
+/// damaged pet is reported as such rather than as an unknown version;
+/// only a pet written before its `version` is read as the current schema.
+#[cfg(feature = "serialization")]
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(untagged)]
+pub enum AnySave {
+    /// Version 2 (current) envelope
+    Current { version: u8, pet: Tamagochi },
+    /// Version 1: the bare pet
+    V1(SaveV1),
+    /// An envelope this build can't read, most likely from a newer version
+    Unknown { version: u8 },
+}
+
+#[cfg(feature = "serialization")]
+impl AnySave {
+    /// The schema version of the save
+    pub fn version(&self) -> u8 {
+        match self {
+            AnySave::Current { version, .. } | AnySave::Unknown { version } => *version,
+            AnySave::V1(_) => 1,
+        }
+    }
+
+    /// Migrate to the current schema, refusing saves from newer versions
+    pub fn migrate(self) -> Result<Tamagochi, SaveError> {
+        match self {
+            AnySave::Current { version, pet } if version == SCHEMA_VERSION => Ok(pet),
+            AnySave::V1(v1) => Ok(v1.migrate()),
+            AnySave::Current { version, .. } | AnySave::Unknown { version } => {
+                Err(SaveError::UnsupportedVersion(version))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serialization")]
+impl<'de> Deserialize<'de> for AnySave {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(AnySaveVisitor)
+    }
+}
+
+#[cfg(feature = "serialization")]
+struct AnySaveVisitor;
+
+#[cfg(feature = "serialization")]
+impl<'de> Visitor<'de> for AnySaveVisitor {
+    type Value = AnySave;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a save file")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<AnySave, A::Error> {
+        let mut key = map.next_key::<String>()?;
+        if let Some(first) = key.take_if(|first| first != "version" && first != "pet") {
+            // No envelope: a version 1 save, the bare pet
+            let replay = Replay { key: Some(first), map };
+            return SaveV1::deserialize(MapAccessDeserializer::new(replay)).map(AnySave::V1);
+        }
+
+        let mut version = None;
+        let mut pet = None;
+        while let Some(name) = key {
+            match (name.as_str(), version) {
+                ("version", _) => version = Some(map.next_value::<u8>()?),
+                ("pet", None) => pet = Some(map.next_value::<Tamagochi>()?),
+                ("pet", Some(v)) if v == SCHEMA_VERSION => pet = Some(map.next_value::<Tamagochi>()?),
+                _ => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+            key = map.next_key()?;
+        }
+
+        let version = version.ok_or_else(|| de::Error::missing_field("version"))?;
+        if version != SCHEMA_VERSION {
+            return Ok(AnySave::Unknown { version });
+        }
+        let pet = pet.ok_or_else(|| de::Error::missing_field("pet"))?;
+        Ok(AnySave::Current { version, pet })
+    }
+}
+
+/// A map whose first key was already read, handing it out again
+#[cfg(feature = "serialization")]
+struct Replay<A> {
+    key: Option<String>,
+    map: A,
+}
+
+#[cfg(feature = "serialization")]
+impl<'de, A: MapAccess<'de>> MapAccess<'de> for Replay<A> {
+    type Error = A::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, A::Error> {
+        match self.key.take() {
+            Some(key) => seed.deserialize(key.into_deserializer()).map(Some),
+            None => self.map.next_key_seed(seed),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, A::Error> {
+        self.map.next_value_seed(seed)
+    }
+}
@@ -0,0 +1,184 @@
+//! Flash storage abstraction and wear-leveled save rotation
+//!
+//! [`Storage`] is the minimal interface to a block of erasable flash
+//! sectors. [`SaveManager`] writes every save to the next sector in turn,
+//! tagged with a sequence number, so wear is spread over all sectors and
+//! the previous save stays intact until the new one is complete. On boot
+//! the newest record with a valid checksum wins, which makes saving safe
+//! against power loss mid-write.
+//!
+//! Record layout at the start of each sector (little endian):
+//!
+//! | Offset | Size | Content                                |
+//! |--------|------|----------------------------------------|
+//! | 0      | 4    | Sequence number                        |
+//! | 4      | 4    | Bitwise NOT of the sequence number     |
+//! | 8      | n    | Binary save (see [`crate::save`])      |
+
+use core::fmt;
+
+#[cfg(feature = "serialization")]
+use serde::{Deserialize, Serialize};
+
+use crate::save::{self, SaveError, SAVE_SIZE};
+use crate::Tamagochi;
+
+const SEQ_SIZE: usize = 8;
+
+/// Bytes needed per sector for one record
+pub const RECORD_SIZE: usize = SEQ_SIZE + SAVE_SIZE;
+
+/// A set of equally sized, individually erasable flash sectors
+///
+/// Like real NOR flash, erased bytes read as `0xFF` and a write may only
+/// be issued to an erased sector.
+pub trait Storage {
+    /// Backend-specific error
+    type Error;
+
+    /// Number of sectors available for saves
+    fn sector_count(&self) -> usize;
+
+    /// Size of one sector in bytes
+    fn sector_size(&self) -> usize;
+
+    /// Read `buf.len()` bytes from the start of a sector
+    fn read(&mut self, sector: usize, buf: &mut [u8]) -> Result<(), Self::Error>;
+
+    /// Erase a sector
+    fn erase(&mut self, sector: usize) -> Result<(), Self::Error>;
+
+    /// Write `data` to the start of an erased sector
+    fn write(&mut self, sector: usize, data: &[u8]) -> Result<(), Self::Error>;
+}
+
+/// Errors from the save manager
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialization", derive(Serialize, Deserialize))]
+pub enum StorageError<E> {
+    /// The backend failed
+    Storage(E),
+    /// Rotation needs at least two sectors
+    TooFewSectors,
+    /// A sector can't hold a record
+    SectorTooSmall,
+}
+
+impl<E: fmt::Debug> fmt::Display for StorageError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Storage(e) => write!(f, "storage error: {:?}", e),
+            StorageError::TooFewSectors => write!(f, "at least two sectors are needed"),
+            StorageError::SectorTooSmall => write!(f, "sector too small for a save record"),
+        }
+    }
+}
+
+/// Rotates saves across all sectors of a [`Storage`]
+pub struct SaveManager<S: Storage> {
+    storage: S,
+    /// Sector and sequence number of the newest valid record
+    newest: Option<(usize, u32)>,
+    /// Whether the sectors were scanned for the newest record yet
+    scanned: bool,
+}
+
+impl<S: Storage> SaveManager<S> {
+    /// Take over a storage backend
+    pub fn new(storage: S) -> Result<Self, StorageError<S::Error>> {
+        if storage.sector_count() < 2 {
+            return Err(StorageError::TooFewSectors);
+        }
+        if storage.sector_size() < RECORD_SIZE {
+            return Err(StorageError::SectorTooSmall);
+        }
+        Ok(Self {
+            storage,
+            newest: None,
+            scanned: false,
+        })
+    }
+
+    /// Give the storage backend back
+    pub fn into_inner(self) -> S {
+        self.storage
+    }
+
+    /// Find and load the newest valid save
+    ///
+    /// Sectors with torn or corrupt records are skipped. Returns `None`
+    /// if no sector holds a valid save.
+    pub fn load(&mut self) -> Result<Option<Tamagochi>, StorageError<S::Error>> {
+        let mut newest: Option<(usize, u32, Tamagochi)> = None;
+
+        for sector in 0..self.storage.sector_count() {
+            let mut record = [0; RECORD_SIZE];
+            self.storage
+                .read(sector, &mut record)
+                .map_err(StorageError::Storage)?;
+
+            let Ok((seq, tama)) = parse_record(&record) else {
+                continue;
+            };
+            if newest.is_none_or(|(_, newest_seq, _)| is_newer(seq, newest_seq)) {
+                newest = Some((sector, seq, tama));
+            }
+        }
+
+        self.newest = newest.map(|(sector, seq, _)| (sector, seq));
+        self.scanned = true;
+        Ok(newest.map(|(_, _, tama)| tama))
+    }
+
+    /// Write a save to the next sector
+    ///
+    /// The sector holding the newest save is never touched, so a power
+    /// loss during this call leaves the previous save loadable.
+    pub fn save(&mut self, tama: &Tamagochi) -> Result<(), StorageError<S::Error>> {
+        if !self.scanned {
+            // Continue numbering after the newest record on flash
+            self.load()?;
+        }
+        let (sector, seq) = match self.newest {
+            Some((sector, seq)) => ((sector + 1) % self.storage.sector_count(), seq.wrapping_add(1)),
+            None => (0, 0),
+        };
+
+        let mut record = [0; RECORD_SIZE];
+        record[..4].copy_from_slice(&seq.to_le_bytes());
+        record[4..8].copy_from_slice(&(!seq).to_le_bytes());
+        // The record has room for exactly one save
+        let _ = save::encode(tama, &mut record[SEQ_SIZE..]);
+
+        self.storage.erase(sector).map_err(StorageError::Storage)?;
+        self.storage
+            .write(sector, &record)
+            .map_err(StorageError::Storage)?;
+        self.newest = Some((sector, seq));
+        Ok(())
+    }
+
+    /// Sequence number of the newest save, if any
+    pub fn sequence(&self) -> Option<u32> {
+        self.newest.map(|(_, seq)| seq)
+    }
+}
+
+/// Whether sequence number `seq` comes after `than`
+///
+/// Compared as serial numbers, so numbering continues correctly when
+/// `save` wraps around after `u32::MAX`.
+fn is_newer(seq: u32, than: u32) -> bool {
+    (seq.wrapping_sub(than) as i32) > 0
+}
+
+/// Validate a record and decode its save
+fn parse_record(record: &[u8; RECORD_SIZE]) -> Result<(u32, Tamagochi), SaveError> {
+    let seq = u32::from_le_bytes([record[0], record[1], record[2], record[3]]);
+    let check = u32::from_le_bytes([record[4], record[5], record[6], record[7]]);
+    if check != !seq {
+        return Err(SaveError::BadChecksum);
+    }
+    let tama = save::decode(&record[SEQ_SIZE..])?;
+    Ok((seq, tama))
+}
@@ -0,0 +1,99 @@
+//! Golden saves that must keep loading forever
+//!
+//! These files were written by schema version 1: `v1.bin` by the first
+//! binary format, `v1.json` by the first release, which serialized the
+//! bare pet with only its first seven fields. Never regenerate them; add
+//! new golden files for new versions instead.
+
+use tamagochi_core::{AdultForm, LifeStage, Mood, Tamagochi};
+
+/// Check the pet stored in every v1 golden file
+fn assert_v1_pet(tama: &Tamagochi) {
+    assert_eq!(tama.stage, LifeStage::Adult(AdultForm::Normal));
+    assert_eq!(tama.hunger, 42);
+    assert_eq!(tama.happiness, 77);
+    assert_eq!(tama.health, 91);
+    assert_eq!(tama.age, 650_000);
+    assert_eq!(tama.care_quality, 63);
+    assert_eq!(tama.weight, 23);
+    assert_eq!(tama.care_mistakes, 3);
+    assert_eq!(tama.incubation, 1850);
+    assert_eq!(tama.mood, Mood::Bored);
+    assert!(tama.alive);
+    // Filled in by the v1 → v2 migration
+    assert_eq!(tama.generation, 1);
+}
+
+#[test]
+fn test_v1_binary_save_loads() {
+    let bytes = include_bytes!("golden/v1.bin");
+    let tama = Tamagochi::from_bytes(bytes).unwrap();
+    assert_v1_pet(&tama);
+}
+
+#[test]
+fn test_v1_binary_save_migrates_to_current_format() {
+    let bytes = include_bytes!("golden/v1.bin");
+    let tama = Tamagochi::from_bytes(bytes).unwrap();
+    let upgraded = tama.to_bytes();
+    assert_eq!(upgraded[4], tamagochi_core::save::FORMAT_VERSION);
+    assert_v1_pet(&Tamagochi::from_bytes(&upgraded).unwrap());
+}
+
+#[cfg(feature = "serialization")]
+mod json {
+    use tamagochi_core::schema::{AnySave, SaveFile};
+    use tamagochi_core::{AdultForm, LifeStage, SaveError, Tamagochi, SCHEMA_VERSION};
+
+    #[test]
+    fn test_v1_json_save_loads() {
+        let json = include_str!("golden/v1.json");
+        let save: AnySave = serde_json::from_str(json).unwrap();
+        assert_eq!(save.version(), 1);
+        let tama = save.migrate().unwrap();
+        assert_eq!(tama.stage, LifeStage::Adult(AdultForm::Normal));
+        assert_eq!(tama.hunger, 42);
+        assert_eq!(tama.happiness, 77);
+        assert_eq!(tama.health, 91);
+        assert_eq!(tama.age, 650_000);
+        assert_eq!(tama.care_quality, 63);
+        assert!(tama.alive);
+
+        // Fields the first release didn't have are those of a new pet
+        let fresh = Tamagochi::new();
+        assert_eq!(tama.weight, fresh.weight);
+        assert_eq!(tama.adult_duration, fresh.adult_duration);
+        assert_eq!(tama.mood, fresh.mood);
+        assert_eq!(tama.generation, 1);
+    }
+
+    #[test]
+    fn test_damaged_current_json_save_is_not_an_unknown_version() {
+        let mut json = serde_json::to_value(SaveFile::new(&Tamagochi::new())).unwrap();
+        json["pet"]["hunger"] = "lots".into();
+        let error = serde_json::from_value::<AnySave>(json).unwrap_err();
+        assert!(error.to_string().contains("lots"), "{}", error);
+
+        let pet_first = r#"{"pet": {"hunger": "lots"}, "version": 2}"#;
+        assert!(serde_json::from_str::<AnySave>(pet_first).is_err());
+        let missing_pet = format!(r#"{{"version": {}}}"#, SCHEMA_VERSION);
+        assert!(serde_json::from_str::<AnySave>(&missing_pet).is_err());
+    }
+
+    #[test]
+    fn test_current_json_round_trip() {
+        let mut tama = Tamagochi::new();
+        tama.generation = 4;
+        let json = serde_json::to_string(&SaveFile::new(&tama)).unwrap();
+        let save: AnySave = serde_json::from_str(&json).unwrap();
+        assert_eq!(save.version(), SCHEMA_VERSION);
+        assert_eq!(save.migrate().unwrap().generation, 4);
+    }
+
+    #[test]
+    fn test_newer_json_save_is_refused() {
+        let json = r#"{"version": 99, "pet": {"something": "new"}}"#;
+        let save: AnySave = serde_json::from_str(json).unwrap();
+        assert_eq!(save.migrate().unwrap_err(), SaveError::UnsupportedVersion(99));
+    }
+}
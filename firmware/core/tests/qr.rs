@@ -0,0 +1,142 @@
+//! Decodes generated QR codes with an independent reader to make sure
+//! they carry the intended text.
+
+use tamagochi_core::qr::{QrCode, QR_SIZE};
+use tamagochi_core::{AdultForm, LifeStage, Tamagochi};
+
+/// Minimal version 1 reader: format, unmasking, error check, alphanumeric
+fn read(qr: &QrCode) -> String {
+    assert_eq!(qr.size(), QR_SIZE);
+    let n = QR_SIZE;
+
+    // Format information from the copy around the top-left finder
+    let mut positions: Vec<(usize, usize)> = (0..6).map(|i| (8, i)).collect();
+    positions.extend([(8, 7), (8, 8), (7, 8)]);
+    positions.extend((9..15).map(|i| (14 - i, 8)));
+    let raw = positions
+        .iter()
+        .enumerate()
+        .fold(0u16, |acc, (i, &(x, y))| acc | (qr.module(x, y) as u16) << i);
+    let (level, mask) = (0..32u16)
+        .find(|&data| {
+            let mut rem = data;
+            for _ in 0..10 {
+                rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+            }
+            ((data << 10) | rem) ^ 0x5412 == raw
+        })
+        .map(|data| (data >> 3, data & 7))
+        .expect("valid format information");
+    assert_eq!(level, 0b00, "error correction level M");
+
+    // Second copy must agree
+    let second = (0..8)
+        .map(|i| (n - 1 - i, 8))
+        .chain((8..15).map(|i| (8, n - 15 + i)))
+        .enumerate()
+        .fold(0u16, |acc, (i, (x, y))| acc | (qr.module(x, y) as u16) << i);
+    assert_eq!(second, raw);
+
+    let function = |x: usize, y: usize| {
+        x == 6 || y == 6 || (x <= 8 && y <= 8) || (x >= n - 8 && y <= 8) || (x <= 8 && y >= n - 8)
+    };
+    let masked = |x: usize, y: usize| match mask {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (x / 3 + y / 2).is_multiple_of(2),
+        5 => (x * y % 2 + x * y % 3) == 0,
+        6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+    };
+
+    // Zigzag through the data area
+    let mut bits = Vec::new();
+    let mut right = n as isize - 1;
+    let mut upward = true;
+    while right > 0 {
+        if right == 6 {
+            right = 5;
+        }
+        for vert in 0..n {
+            let y = if upward { n - 1 - vert } else { vert };
+            for x in [right as usize, right as usize - 1] {
+                if !function(x, y) {
+                    bits.push(qr.module(x, y) ^ masked(x, y));
+                }
+            }
+        }
+        upward = !upward;
+        right -= 2;
+    }
+    let codewords: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |acc, &b| acc << 1 | b as u8))
+        .collect();
+    assert_eq!(codewords.len(), 26);
+
+    // All ten syndromes vanish for an error-free codeword
+    let mul = |mut a: u8, mut b: u8| {
+        let mut product = 0;
+        while b != 0 {
+            if b & 1 != 0 {
+                product ^= a;
+            }
+            a = if a & 0x80 != 0 { (a << 1) ^ 0x1d } else { a << 1 };
+            b >>= 1;
+        }
+        product
+    };
+    let mut alpha_i = 1;
+    for _ in 0..10 {
+        let syndrome = codewords.iter().fold(0, |acc, &c| mul(acc, alpha_i) ^ c);
+        assert_eq!(syndrome, 0, "error correction codewords");
+        alpha_i = mul(alpha_i, 2);
+    }
+
+    // Alphanumeric segment
+    let mut stream = codewords[..16]
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 != 0));
+    let mut take = |count: usize| (0..count).fold(0usize, |acc, _| acc << 1 | stream.next().unwrap() as usize);
+    assert_eq!(take(4), 0b0010, "alphanumeric mode");
+    let len = take(9);
+    let charset: Vec<char> = "0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ $%*+-./:".chars().collect();
+    let mut text = String::new();
+    for _ in 0..len / 2 {
+        let pair = take(11);
+        text.push(charset[pair / 45]);
+        text.push(charset[pair % 45]);
+    }
+    if len % 2 == 1 {
+        text.push(charset[take(6)]);
+    }
+    text
+}
+
+#[test]
+fn test_decodes_plain_text() {
+    for text in ["", "A", "HELLO WORLD", "0123456789ABCDEFGHIJ"] {
+        assert_eq!(read(&QrCode::encode(text).unwrap()), text);
+    }
+}
+
+#[test]
+fn test_decodes_passcodes() {
+    let mut tama = Tamagochi::new();
+    let codes = [tama.to_passcode(), {
+        tama.stage = LifeStage::Adult(AdultForm::Premium);
+        tama.age = 900_000;
+        tama.care_quality = 91;
+        tama.generation = 4;
+        tama.to_passcode()
+    }];
+
+    for code in codes {
+        let text = read(&code.to_qr());
+        assert_eq!(text, code.to_string());
+        let decoded = Tamagochi::from_passcode(&text).unwrap();
+        assert_eq!(decoded.to_passcode(), code);
+    }
+}
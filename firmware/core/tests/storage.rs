@@ -0,0 +1,160 @@
+//! Save rotation against an in-memory flash emulator
+
+use tamagochi_core::storage::{SaveManager, Storage, StorageError, RECORD_SIZE};
+use tamagochi_core::Tamagochi;
+
+const SECTOR_SIZE: usize = 64;
+
+#[derive(Debug, PartialEq)]
+enum FlashError {
+    /// Simulated power loss during a write
+    PowerLoss,
+    /// Write to a sector that wasn't erased
+    NotErased,
+}
+
+/// NOR-flash emulator: erased bytes are 0xFF and writes can only clear bits
+struct MemFlash {
+    sectors: Vec<[u8; SECTOR_SIZE]>,
+    erase_counts: Vec<u32>,
+    /// Power is lost after this many bytes of the next write
+    tear_after: Option<usize>,
+}
+
+impl MemFlash {
+    fn new(sectors: usize) -> Self {
+        Self {
+            sectors: vec![[0xff; SECTOR_SIZE]; sectors],
+            erase_counts: vec![0; sectors],
+            tear_after: None,
+        }
+    }
+}
+
+impl Storage for MemFlash {
+    type Error = FlashError;
+
+    fn sector_count(&self) -> usize {
+        self.sectors.len()
+    }
+
+    fn sector_size(&self) -> usize {
+        SECTOR_SIZE
+    }
+
+    fn read(&mut self, sector: usize, buf: &mut [u8]) -> Result<(), FlashError> {
+        buf.copy_from_slice(&self.sectors[sector][..buf.len()]);
+        Ok(())
+    }
+
+    fn erase(&mut self, sector: usize) -> Result<(), FlashError> {
+        self.sectors[sector] = [0xff; SECTOR_SIZE];
+        self.erase_counts[sector] += 1;
+        Ok(())
+    }
+
+    fn write(&mut self, sector: usize, data: &[u8]) -> Result<(), FlashError> {
+        if self.sectors[sector][..data.len()].iter().any(|&b| b != 0xff) {
+            return Err(FlashError::NotErased);
+        }
+        let len = self.tear_after.take().unwrap_or(data.len()).min(data.len());
+        for (cell, byte) in self.sectors[sector].iter_mut().zip(&data[..len]) {
+            *cell &= byte;
+        }
+        if len < data.len() {
+            return Err(FlashError::PowerLoss);
+        }
+        Ok(())
+    }
+}
+
+fn pet_aged(age: u32) -> Tamagochi {
+    let mut tama = Tamagochi::new();
+    tama.age = age;
+    tama
+}
+
+#[test]
+fn test_empty_flash_has_no_save() {
+    let mut manager = SaveManager::new(MemFlash::new(4)).unwrap();
+    assert!(manager.load().unwrap().is_none());
+}
+
+#[test]
+fn test_newest_save_wins_after_reboot() {
+    let mut manager = SaveManager::new(MemFlash::new(4)).unwrap();
+    for age in 1..=10 {
+        manager.save(&pet_aged(age)).unwrap();
+    }
+
+    // Reboot
+    let mut manager = SaveManager::new(manager.into_inner()).unwrap();
+    assert_eq!(manager.load().unwrap().unwrap().age, 10);
+    assert_eq!(manager.sequence(), Some(9));
+}
+
+#[test]
+fn test_newest_save_wins_across_sequence_wrap() {
+    let mut flash = MemFlash::new(3);
+    for (sector, (seq, age)) in [(u32::MAX - 1, 1), (u32::MAX, 2), (0, 3)].into_iter().enumerate() {
+        let mut record = Vec::new();
+        record.extend(seq.to_le_bytes());
+        record.extend((!seq).to_le_bytes());
+        record.extend(pet_aged(age).to_bytes());
+        flash.write(sector, &record).unwrap();
+    }
+
+    let mut manager = SaveManager::new(flash).unwrap();
+    assert_eq!(manager.load().unwrap().unwrap().age, 3);
+    assert_eq!(manager.sequence(), Some(0));
+
+    // The next save overwrites the oldest record and wins after a reboot
+    manager.save(&pet_aged(4)).unwrap();
+    let mut manager = SaveManager::new(manager.into_inner()).unwrap();
+    assert_eq!(manager.load().unwrap().unwrap().age, 4);
+    assert_eq!(manager.sequence(), Some(1));
+}
+
+#[test]
+fn test_saves_are_spread_over_all_sectors() {
+    let mut manager = SaveManager::new(MemFlash::new(4)).unwrap();
+    for age in 0..40 {
+        manager.save(&pet_aged(age)).unwrap();
+    }
+    assert_eq!(manager.into_inner().erase_counts, vec![10; 4]);
+}
+
+#[test]
+fn test_torn_write_keeps_previous_save() {
+    let mut manager = SaveManager::new(MemFlash::new(3)).unwrap();
+    manager.save(&pet_aged(1)).unwrap();
+    manager.save(&pet_aged(2)).unwrap();
+
+    for tear_after in 0..RECORD_SIZE {
+        let mut flash = manager.into_inner();
+        flash.tear_after = Some(tear_after);
+        let mut torn = SaveManager::new(flash).unwrap();
+        assert_eq!(
+            torn.save(&pet_aged(3)),
+            Err(StorageError::Storage(FlashError::PowerLoss))
+        );
+
+        // Reboot: the interrupted save is ignored
+        let mut rebooted = SaveManager::new(torn.into_inner()).unwrap();
+        assert_eq!(rebooted.load().unwrap().unwrap().age, 2, "torn after {}", tear_after);
+        manager = rebooted;
+    }
+
+    // Saving continues normally afterwards
+    manager.save(&pet_aged(3)).unwrap();
+    let mut rebooted = SaveManager::new(manager.into_inner()).unwrap();
+    assert_eq!(rebooted.load().unwrap().unwrap().age, 3);
+}
+
+#[test]
+fn test_needs_two_sectors() {
+    assert!(matches!(
+        SaveManager::new(MemFlash::new(1)),
+        Err(StorageError::TooFewSectors)
+    ));
+}
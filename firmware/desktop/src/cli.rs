@@ -0,0 +1,352 @@
+//! Command-line argument parsing
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+
+use crate::core::Action;
+use crate::daemon::{Frontends, Request};
+use crate::prompt;
+
+/// What the program was asked to do
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Play interactively (the default)
+    Interactive,
+    /// List save slots
+    List,
+    /// Create a new slot with a fresh egg
+    New(String),
+    /// Make a slot the active one
+    Switch(String),
+    /// Delete a slot
+    Delete(String),
+    /// Rename a slot
+    Rename(String, String),
+    /// Print the passcode of a slot (the active one if not given),
+    /// optionally as a QR code
+    ExportCode(Option<String>, bool),
+    /// Create a slot from a passcode
+    ImportCode(String, Option<String>),
+    /// Print a JSON Schema
+    Schema(SchemaKind),
+    /// Perform an action on a slot's pet (the active one if not given)
+    /// and exit
+    Act(Action, Option<String>),
+    /// Print the status of a slot's pet and exit
+    Status(Option<String>, StatusFormat),
+    /// Print a short indicator for shell prompts, optionally with a
+    /// format string
+    Prompt(Option<String>, Option<String>),
+    /// Print the prompt snippet for a shell or tmux
+    PromptInit(String),
+    /// Keep a slot's pet alive in the background (`daemon`, `web` and
+    /// `mqtt`), optionally also serving it over HTTP or MQTT
+    Daemon(Option<String>, Frontends),
+    /// Send a command to the daemon
+    Client(Request),
+    /// Show usage
+    Help,
+}
+
+/// Formats with a JSON Schema
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SchemaKind {
+    /// Slot files
+    Save,
+    /// Lines of the `--events-out` stream
+    Events,
+}
+
+/// How `status` prints
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StatusFormat {
+    /// The status box
+    Text,
+    /// A JSON object
+    Json,
+    /// One line
+    Short,
+}
+
+/// Parsed command line
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    pub command: Command,
+    /// Load saves that were edited outside the game
+    pub allow_tampered: bool,
+    /// Append game events and actions to this file as NDJSON
+    pub events_out: Option<PathBuf>,
+}
+
+/// Where `web` listens without `--http`
+pub const DEFAULT_WEB_ADDR: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), 8080);
+
+pub const USAGE: &str = "\
+Usage: tamagochi-cli [OPTIONS] [COMMAND]
+
+Commands:
+  (none)               Pick a save slot and play interactively
+  list                 List save slots
+  new <name>           Create a slot with a fresh egg and make it active
+  switch <name>        Make a slot the active one
+  delete <name>        Delete a slot
+  rename <old> <new>   Rename a slot
+  export-code [name] [--qr]
+                       Show a shareable passcode for a pet (default: active slot),
+                       with --qr also as a QR code
+  import-code <code> [name]
+                       Create a slot from a passcode (default name: imported)
+  schema <save|events> Print the JSON Schema of slot files or of the event stream
+  feed|play|medicine|clean|warm [name]
+                       Care for a pet (default: active slot) and exit
+  status [name] [--json|--short]
+                       Print a pet's status (default: active slot) and exit
+  prompt [name] [--format <format>]
+                       Print a tiny indicator like 🐣♥♥♡ for shell prompts and tmux
+  prompt --init <bash|zsh|tmux>
+                       Print a snippet that adds the indicator to a prompt or tmux
+  daemon [name] [--http <port|addr>]
+                       Keep a pet (default: active slot) alive in the background,
+                       controlled over a Unix socket; with --http also over HTTP
+                       (a bare port binds to 127.0.0.1)
+  web [name] [--http <port|addr>]
+                       Like daemon, serving a page to play in the browser
+                       (default: http://127.0.0.1:8080/)
+  mqtt [name] --broker <host:port> [--http <port|addr>]
+                       Like daemon, publishing the pet to an MQTT broker with
+                       Home Assistant discovery and taking commands from it
+  client <command>     Send feed, play, medicine, clean, warm, status, subscribe
+                       or shutdown to the daemon and print the JSON replies
+  help                 Show this help
+
+Options:
+  --allow-tampered     Load saves edited outside the game (the pet is marked as modified)
+  --events-out <file>  Append every game event and action to <file> as JSON lines
+
+Exit status of the one-shot commands (feed, ..., status):
+  0  the pet is fine      3  the pet needs attention
+  1  error                4  the pet is dead
+  2  usage error          5  the action isn't available right now
+
+Saves are kept in $TAMAGOCHI_HOME (default: ~/.local/share/tamagochi).";
+
+/// Parse the arguments (without the program name)
+pub fn parse(args: &[String]) -> Result<Args, String> {
+    let mut allow_tampered = false;
+    let mut events_out = None;
+    let mut rest = Vec::new();
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        match arg {
+            "--allow-tampered" => allow_tampered = true,
+            "--events-out" => {
+                let path = args.next().ok_or("--events-out needs a file")?;
+                events_out = Some(PathBuf::from(path));
+            }
+            _ => rest.push(arg),
+        }
+    }
+
+    Ok(Args {
+        command: parse_command(&rest)?,
+        allow_tampered,
+        events_out,
+    })
+}
+
+fn parse_command(args: &[&str]) -> Result<Command, String> {
+    match args {
+        [] => Ok(Command::Interactive),
+        ["list"] => Ok(Command::List),
+        ["new", name] => Ok(Command::New(name.to_string())),
+        ["switch", name] => Ok(Command::Switch(name.to_string())),
+        ["delete", name] => Ok(Command::Delete(name.to_string())),
+        ["rename", old, new] => Ok(Command::Rename(old.to_string(), new.to_string())),
+        ["export-code", rest @ ..] => {
+            let qr = rest.contains(&"--qr");
+            match rest.iter().filter(|arg| **arg != "--qr").collect::<Vec<_>>().as_slice() {
+                [] => Ok(Command::ExportCode(None, qr)),
+                [name] => Ok(Command::ExportCode(Some(name.to_string()), qr)),
+                _ => Err("wrong number of arguments for 'export-code'".to_string()),
+            }
+        }
+        ["import-code", code] => Ok(Command::ImportCode(code.to_string(), None)),
+        ["import-code", code, name] => Ok(Command::ImportCode(code.to_string(), Some(name.to_string()))),
+        ["schema", "save"] => Ok(Command::Schema(SchemaKind::Save)),
+        ["schema", "events"] => Ok(Command::Schema(SchemaKind::Events)),
+        ["schema", kind] => Err(format!("unknown schema '{}' (expected 'save' or 'events')", kind)),
+        [action @ ("feed" | "play" | "medicine" | "clean" | "warm"), rest @ ..] => {
+            let action = match *action {
+                "feed" => Action::Feed,
+                "play" => Action::Play,
+                "medicine" => Action::Medicine,
+                "clean" => Action::Clean,
+                _ => Action::Warm,
+            };
+            match rest {
+                [] => Ok(Command::Act(action, None)),
+                [name] => Ok(Command::Act(action, Some(name.to_string()))),
+                _ => Err(format!("wrong number of arguments for '{}'", args[0])),
+            }
+        }
+        ["status", rest @ ..] => {
+            let mut format = StatusFormat::Text;
+            let mut names = Vec::new();
+            for arg in rest {
+                match *arg {
+                    "--json" => format = StatusFormat::Json,
+                    "--short" => format = StatusFormat::Short,
+                    _ => names.push(arg.to_string()),
+                }
+            }
+            match names.as_slice() {
+                [] => Ok(Command::Status(None, format)),
+                [name] => Ok(Command::Status(Some(name.clone()), format)),
+                _ => Err("wrong number of arguments for 'status'".to_string()),
+            }
+        }
+        ["prompt", "--init", shell] => {
+            if prompt::SNIPPETS.iter().any(|(name, _)| name == shell) {
+                Ok(Command::PromptInit(shell.to_string()))
+            } else {
+                Err(format!("no prompt snippet for '{}' (expected bash, zsh or tmux)", shell))
+            }
+        }
+        ["prompt", rest @ ..] => {
+            let mut format = None;
+            let mut names = Vec::new();
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match *arg {
+                    "--format" => {
+                        let value = rest.next().ok_or("--format needs a format string")?;
+                        format = Some(value.to_string());
+                    }
+                    _ => names.push(arg.to_string()),
+                }
+            }
+            match names.as_slice() {
+                [] => Ok(Command::Prompt(None, format)),
+                [name] => Ok(Command::Prompt(Some(name.clone()), format)),
+                _ => Err("wrong number of arguments for 'prompt'".to_string()),
+            }
+        }
+        [command @ ("daemon" | "web" | "mqtt"), rest @ ..] => {
+            let mut frontends = Frontends::default();
+            let mut names = Vec::new();
+            let mut rest = rest.iter();
+            while let Some(arg) = rest.next() {
+                match *arg {
+                    "--http" => {
+                        let value = rest.next().ok_or("--http needs a port or address")?;
+                        frontends.http = Some(parse_http_addr(value)?);
+                    }
+                    "--broker" if *command == "mqtt" => {
+                        let value = rest.next().ok_or("--broker needs a host:port")?;
+                        frontends.mqtt = Some(value.to_string());
+                    }
+                    _ => names.push(arg.to_string()),
+                }
+            }
+            let name = match names.as_slice() {
+                [] => None,
+                [name] => Some(name.clone()),
+                _ => return Err(format!("wrong number of arguments for '{}'", command)),
+            };
+            match *command {
+                "web" => {
+                    frontends.http.get_or_insert(DEFAULT_WEB_ADDR);
+                }
+                "mqtt" if frontends.mqtt.is_none() => return Err("'mqtt' needs --broker <host:port>".to_string()),
+                _ => {}
+            }
+            Ok(Command::Daemon(name, frontends))
+        }
+        ["client", command] => Request::ALL
+            .iter()
+            .find(|(name, _)| name == command)
+            .map(|&(_, request)| Command::Client(request))
+            .ok_or_else(|| format!("unknown daemon command '{}'", command)),
+        ["help" | "-h" | "--help"] => Ok(Command::Help),
+        [cmd @ ("new" | "switch" | "delete" | "rename" | "import-code" | "schema" | "client"), ..] => {
+            Err(format!("wrong number of arguments for '{}'", cmd))
+        }
+        [cmd, ..] => Err(format!("unknown command '{}'", cmd)),
+    }
+}
+
+/// A port on localhost, or a full address to listen elsewhere
+fn parse_http_addr(value: &str) -> Result<SocketAddr, String> {
+    if let Ok(port) = value.parse::<u16>() {
+        return Ok(SocketAddr::from((Ipv4Addr::LOCALHOST, port)));
+    }
+    value
+        .parse()
+        .map_err(|_| format!("invalid --http address '{}' (expected a port or ip:port)", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_str(args: &str) -> Result<Args, String> {
+        parse(&args.split_whitespace().map(String::from).collect::<Vec<_>>())
+    }
+
+    fn command(args: &str) -> Command {
+        parse_str(args).unwrap().command
+    }
+
+    #[test]
+    fn test_status_formats() {
+        assert_eq!(command("status"), Command::Status(None, StatusFormat::Text));
+        assert_eq!(command("status --json"), Command::Status(None, StatusFormat::Json));
+        assert_eq!(command("status pet --short"), Command::Status(Some("pet".to_string()), StatusFormat::Short));
+        assert_eq!(command("status --json pet"), Command::Status(Some("pet".to_string()), StatusFormat::Json));
+        assert!(parse_str("status a b").is_err());
+    }
+
+    #[test]
+    fn test_global_options_go_anywhere() {
+        let args = parse_str("feed --events-out /tmp/events.ndjson pet --allow-tampered").unwrap();
+        assert_eq!(args.command, Command::Act(Action::Feed, Some("pet".to_string())));
+        assert_eq!(args.events_out, Some(PathBuf::from("/tmp/events.ndjson")));
+        assert!(args.allow_tampered);
+
+        let args = parse_str("").unwrap();
+        assert_eq!(args.command, Command::Interactive);
+        assert_eq!(args.events_out, None);
+        assert!(!args.allow_tampered);
+        assert!(parse_str("status --events-out").is_err());
+    }
+
+    #[test]
+    fn test_daemon_frontends() {
+        let Command::Daemon(name, frontends) = command("daemon") else { panic!() };
+        assert_eq!((name, frontends), (None, Frontends::default()));
+
+        // A bare port stays on localhost
+        let Command::Daemon(name, frontends) = command("daemon pet --http 9000") else { panic!() };
+        assert_eq!(name.as_deref(), Some("pet"));
+        assert_eq!(frontends.http, Some("127.0.0.1:9000".parse().unwrap()));
+        let Command::Daemon(_, frontends) = command("daemon --http 0.0.0.0:9000") else { panic!() };
+        assert_eq!(frontends.http, Some("0.0.0.0:9000".parse().unwrap()));
+        assert!(parse_str("daemon --http").is_err());
+        assert!(parse_str("daemon --http localhost").is_err());
+        assert!(parse_str("daemon --broker localhost:1883").is_err());
+    }
+
+    #[test]
+    fn test_web_and_mqtt() {
+        let Command::Daemon(None, frontends) = command("web") else { panic!() };
+        assert_eq!(frontends.http, Some(DEFAULT_WEB_ADDR));
+        let Command::Daemon(None, frontends) = command("web --http 3000") else { panic!() };
+        assert_eq!(frontends.http, Some("127.0.0.1:3000".parse().unwrap()));
+
+        let Command::Daemon(Some(name), frontends) = command("mqtt pet --broker localhost:1883") else { panic!() };
+        assert_eq!(name, "pet");
+        assert_eq!(frontends.mqtt.as_deref(), Some("localhost:1883"));
+        assert_eq!(frontends.http, None);
+        assert!(parse_str("mqtt pet").is_err());
+    }
+}
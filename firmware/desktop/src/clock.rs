@@ -0,0 +1,247 @@
+//! Offline progress and clock-manipulation detection
+//!
+//! When a slot is loaded, the pet catches up on the time that passed
+//! since its last save. Setting the system clock would make that a cheat
+//! (or a way to kill a pet), so every save keeps a trail of clock
+//! readings. On load the wall clock is checked against it:
+//!
+//! - A wall clock behind the last save means the clock was set back; no
+//!   time is applied.
+//! - Within the same boot, the system uptime tells how much time really
+//!   passed. If the wall clock moved by a different amount, the uptime
+//!   wins.
+//! - Catch-up is capped, so a jump we can't prove wrong (e.g. across a
+//!   reboot) still can't age a pet by weeks.
+//!
+//! Anomalies are reported to the player instead of being applied
+//! silently. The trail is part of the signed slot metadata.
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::slots;
+
+/// How far the wall clock may drift from the uptime before we call it a
+/// clock change (NTP corrections, rounding)
+const TOLERANCE_SECS: u64 = 120;
+
+/// Clock readings at the last save
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct ClockTrail {
+    /// Wall time (Unix seconds)
+    pub wall: u64,
+    /// System uptime (seconds), where the platform provides it
+    pub uptime: Option<u64>,
+    /// Number of saves so far; 0 means there is no trail yet
+    pub saves: u64,
+}
+
+impl ClockTrail {
+    /// Record a save at `now`
+    pub fn record(&mut self, now: Reading) {
+        self.wall = now.wall;
+        self.uptime = now.uptime;
+        self.saves += 1;
+    }
+}
+
+/// The clocks right now
+#[derive(Clone, Copy, Debug)]
+pub struct Reading {
+    pub wall: u64,
+    pub uptime: Option<u64>,
+}
+
+impl Reading {
+    pub fn now() -> Self {
+        Self {
+            wall: slots::now(),
+            uptime: uptime(),
+        }
+    }
+}
+
+/// Something off with the clock since the last save
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClockAnomaly {
+    /// The wall clock is behind the last save
+    Backwards { secs: u64 },
+    /// The wall clock moved a different amount than really passed
+    Jumped { wall: i64, actual: u64 },
+    /// More time passed than the catch-up limit allows
+    Capped { elapsed: u64, applied: u64 },
+}
+
+impl fmt::Display for ClockAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ClockAnomaly::Backwards { secs } => write!(
+                f,
+                "The clock is {} behind your last save, so no time passed for your pet.",
+                format_duration(secs)
+            ),
+            ClockAnomaly::Jumped { wall, actual } => write!(
+                f,
+                "The clock moved {}{} but only {} really passed; only the real time counts.",
+                if wall < 0 { "back " } else { "" },
+                format_duration(wall.unsigned_abs()),
+                format_duration(actual)
+            ),
+            ClockAnomaly::Capped { elapsed, applied } => write!(
+                f,
+                "You were away for {}; your pet only lived through the last {} \
+                 (max_catch_up_minutes in config.toml).",
+                format_duration(elapsed),
+                format_duration(applied)
+            ),
+        }
+    }
+}
+
+/// Time to apply on load
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CatchUp {
+    /// Seconds (ticks) to simulate
+    pub ticks: u64,
+    pub anomalies: Vec<ClockAnomaly>,
+}
+
+/// Work out how much time to apply since the last save
+pub fn catch_up(trail: &ClockTrail, now: Reading, max_secs: u64) -> CatchUp {
+    if trail.saves == 0 {
+        // Nothing to compare against yet
+        return CatchUp::default();
+    }
+
+    let mut anomalies = Vec::new();
+    let wall = now.wall as i64 - trail.wall as i64;
+    // Uptime only counts if the system didn't reboot in between
+    let actual = match (trail.uptime, now.uptime) {
+        (Some(then), Some(now)) if now >= then => Some(now - then),
+        _ => None,
+    };
+
+    let elapsed = match actual {
+        Some(actual) => {
+            if wall.abs_diff(actual as i64) > TOLERANCE_SECS {
+                anomalies.push(ClockAnomaly::Jumped { wall, actual });
+            }
+            actual
+        }
+        None if wall < 0 => {
+            anomalies.push(ClockAnomaly::Backwards {
+                secs: wall.unsigned_abs(),
+            });
+            0
+        }
+        None => wall as u64,
+    };
+
+    let ticks = elapsed.min(max_secs);
+    if ticks < elapsed {
+        anomalies.push(ClockAnomaly::Capped {
+            elapsed,
+            applied: ticks,
+        });
+    }
+    CatchUp { ticks, anomalies }
+}
+
+/// Seconds since boot, from `/proc/uptime` on Linux
+fn uptime() -> Option<u64> {
+    let uptime = std::fs::read_to_string("/proc/uptime").ok()?;
+    let secs: f64 = uptime.split_whitespace().next()?.parse().ok()?;
+    Some(secs as u64)
+}
+
+/// A duration like `3d 4h`, `2h 5m` or `40s`
+pub fn format_duration(secs: u64) -> String {
+    let (days, hours, mins) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m", mins)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+
+    fn trail(wall: u64, uptime: Option<u64>) -> ClockTrail {
+        ClockTrail {
+            wall,
+            uptime,
+            saves: 3,
+        }
+    }
+
+    fn at(wall: u64, uptime: Option<u64>) -> Reading {
+        Reading { wall, uptime }
+    }
+
+    #[test]
+    fn test_applies_honest_elapsed_time() {
+        let result = catch_up(&trail(10_000, Some(500)), at(10_600, Some(1100)), HOUR);
+        assert_eq!(result, CatchUp { ticks: 600, anomalies: vec![] });
+
+        // Across a reboot only the wall clock is left
+        let result = catch_up(&trail(10_000, Some(5000)), at(10_600, Some(20)), HOUR);
+        assert_eq!(result.ticks, 600);
+        assert!(result.anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_no_trail_applies_nothing() {
+        let result = catch_up(&ClockTrail::default(), at(10_000, None), HOUR);
+        assert_eq!(result, CatchUp::default());
+    }
+
+    #[test]
+    fn test_backwards_clock_applies_nothing() {
+        let result = catch_up(&trail(10_000, None), at(9_000, None), HOUR);
+        assert_eq!(result.ticks, 0);
+        assert_eq!(result.anomalies, vec![ClockAnomaly::Backwards { secs: 1000 }]);
+    }
+
+    #[test]
+    fn test_uptime_beats_a_changed_wall_clock() {
+        // Clock set a day forward, but only ten minutes passed
+        let result = catch_up(&trail(10_000, Some(500)), at(10_600 + 86_400, Some(1100)), HOUR);
+        assert_eq!(result.ticks, 600);
+        assert_eq!(
+            result.anomalies,
+            vec![ClockAnomaly::Jumped { wall: 87_000, actual: 600 }]
+        );
+
+        // Clock set back within the same boot
+        let result = catch_up(&trail(10_000, Some(500)), at(9_000, Some(1100)), HOUR);
+        assert_eq!(result.ticks, 600);
+        assert_eq!(result.anomalies, vec![ClockAnomaly::Jumped { wall: -1000, actual: 600 }]);
+    }
+
+    #[test]
+    fn test_long_absence_is_capped() {
+        let result = catch_up(&trail(10_000, None), at(10_000 + 5 * HOUR, None), HOUR);
+        assert_eq!(result.ticks, HOUR);
+        assert_eq!(
+            result.anomalies,
+            vec![ClockAnomaly::Capped { elapsed: 5 * HOUR, applied: HOUR }]
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(42), "42s");
+        assert_eq!(format_duration(125), "2m");
+        assert_eq!(format_duration(2 * HOUR + 300), "2h 5m");
+        assert_eq!(format_duration(3 * 86_400 + 4 * HOUR), "3d 4h");
+    }
+}
@@ -0,0 +1,140 @@
+//! Non-interactive subcommands
+
+use crate::cli::SchemaKind;
+use crate::core::Tamagochi;
+use crate::events;
+use crate::slots::{self, format_timestamp, SlotError, SlotStore};
+use crate::sprites;
+
+/// `list`: show all slots with their pets
+pub fn list(store: &SlotStore) -> Result<(), SlotError> {
+    let names = store.names()?;
+    if names.is_empty() {
+        println!("No save slots yet. Create one with: tamagochi-cli new <name>");
+        return Ok(());
+    }
+
+    let active = store.active();
+    println!(
+        "  {:<16} {:<18} {:>4} {:>4}  {:<16}  {:<16}",
+        "NAME", "STAGE", "DAYS", "GEN", "CREATED", "LAST PLAYED"
+    );
+    for name in names {
+        let marker = if active.as_deref() == Some(name.as_str()) { "*" } else { " " };
+        match store.load(&name) {
+            Ok(slot) => {
+                let stage = if slot.pet.alive {
+                    format!("{:?}", slot.pet.stage)
+                } else {
+                    "†".to_string()
+                };
+                println!(
+                    "{} {:<16} {:<18} {:>4} {:>4}  {:<16}  {:<16}{}",
+                    marker,
+                    name,
+                    stage,
+                    slot.pet.age / 86400,
+                    slot.pet.generation,
+                    format_timestamp(slot.meta.created),
+                    format_timestamp(slot.meta.last_played),
+                    if slot.meta.modified { "  modified" } else { "" },
+                );
+            }
+            Err(SlotError::Tampered(_)) => {
+                println!("{} {:<16} (edited outside the game)", marker, name)
+            }
+            Err(e) => println!("{} {:<16} ({})", marker, name, e),
+        }
+    }
+    println!("\nSaves are in {}", store.dir().display());
+    Ok(())
+}
+
+/// `new <name>`: create a slot and make it active
+pub fn new(store: &SlotStore, name: &str) -> Result<(), SlotError> {
+    store.create(name)?;
+    store.set_active(name)?;
+    println!("🥚 Created save slot '{}' with a fresh egg.", name);
+    Ok(())
+}
+
+/// `switch <name>`: make a slot active
+pub fn switch(store: &SlotStore, name: &str) -> Result<(), SlotError> {
+    store.set_active(name)?;
+    println!("Switched to save slot '{}'.", name);
+    Ok(())
+}
+
+/// `delete <name>`: remove a slot
+pub fn delete(store: &SlotStore, name: &str) -> Result<(), SlotError> {
+    store.delete(name)?;
+    println!("Deleted save slot '{}'.", name);
+    Ok(())
+}
+
+/// `rename <old> <new>`: rename a slot
+pub fn rename(store: &SlotStore, old: &str, new: &str) -> Result<(), SlotError> {
+    store.rename(old, new)?;
+    println!("Renamed save slot '{}' to '{}'.", old, new);
+    Ok(())
+}
+
+/// `export-code [name] [--qr]`: print a slot's passcode
+pub fn export_code(store: &SlotStore, name: Option<&str>, qr: bool) -> Result<(), SlotError> {
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => store.active().ok_or(SlotError::NoActive)?,
+    };
+    let slot = store.load(&name)?;
+    let code = slot.pet.to_passcode();
+    if qr {
+        print!("{}", sprites::get_qr_sprite(&code.to_qr()));
+    }
+    println!("{}", code);
+    Ok(())
+}
+
+/// `import-code <code> [name]`: create a slot from a passcode and make it
+/// active, marked as modified
+pub fn import_code(store: &SlotStore, code: &str, name: &str) -> Result<(), SlotError> {
+    let pet = Tamagochi::from_passcode(code).map_err(SlotError::Passcode)?;
+    store.import(name, pet)?;
+    store.set_active(name)?;
+    println!(
+        "📥 Imported a pet ({:?}, generation {}) into save slot '{}'.",
+        pet.stage, pet.generation, name
+    );
+    println!("   Passcodes can't be verified, so it's marked as modified.");
+    Ok(())
+}
+
+/// `schema <save|events>`: print a JSON Schema
+pub fn schema(kind: SchemaKind) -> Result<(), SlotError> {
+    println!("{}", schema_json(kind));
+    Ok(())
+}
+
+fn schema_json(kind: SchemaKind) -> String {
+    let schema = match kind {
+        SchemaKind::Save => slots::file_schema(),
+        SchemaKind::Events => events::schema(),
+    };
+    serde_json::to_string_pretty(&schema).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_committed_schemas_are_current() {
+        // Regenerate with `tamagochi-cli schema <kind> > schema/<kind>.schema.json`
+        let committed = [
+            (SchemaKind::Save, include_str!("../schema/save.schema.json")),
+            (SchemaKind::Events, include_str!("../schema/events.schema.json")),
+        ];
+        for (kind, file) in committed {
+            assert_eq!(schema_json(kind), file.trim_end(), "{:?} schema is out of date", kind);
+        }
+    }
+}
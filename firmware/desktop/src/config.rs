@@ -0,0 +1,161 @@
+//! User settings from `<data dir>/config.toml`
+//!
+//! Every setting is optional; a missing file means all defaults.
+//!
+//! ```toml
+//! # Most time a pet catches up on after the game was closed (0 = none)
+//! max_catch_up_minutes = 60
+//!
+//! [prompt]
+//! # Line printed by `tamagochi-cli prompt` (placeholders: see prompt.rs)
+//! format = "{stage}{hearts} {alerts}"
+//!
+//! [mqtt]
+//! # Topics of `tamagochi-cli mqtt` (see mqtt.rs)
+//! base_topic = "tamagochi"
+//! discovery_prefix = "homeassistant"
+//! # The broker drops the bridge after this long without a packet
+//! keep_alive_secs = 60
+//! # Credentials, if the broker wants them
+//! username = "tamagochi"
+//! password = "secret"
+//!
+//! [hooks]
+//! # Shell commands run on events (keys and variables: see hooks.rs)
+//! evolution = 'notify-send Tamagochi "Evolved into $TAMAGOCHI_STAGE"'
+//! needs_attention_minutes = 10
+//! ```
+
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::prompt;
+use crate::slots::SlotError;
+
+/// Settings
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Most offline time applied when a slot is loaded
+    pub max_catch_up_minutes: u64,
+    /// `[prompt]` section
+    pub prompt: PromptConfig,
+    /// `[mqtt]` section
+    pub mqtt: MqttConfig,
+    /// `[hooks]` section
+    pub hooks: HooksConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            max_catch_up_minutes: 60,
+            prompt: PromptConfig::default(),
+            mqtt: MqttConfig::default(),
+            hooks: HooksConfig::default(),
+        }
+    }
+}
+
+/// Settings of `tamagochi-cli prompt`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PromptConfig {
+    /// Format string with placeholders
+    pub format: String,
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            format: prompt::DEFAULT_FORMAT.to_string(),
+        }
+    }
+}
+
+/// Settings of `tamagochi-cli mqtt`
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MqttConfig {
+    /// Topics are `<base_topic>/<slot>/...`
+    pub base_topic: String,
+    /// Where Home Assistant looks for discovery payloads
+    pub discovery_prefix: String,
+    /// Longest time without packets before either side gives up on the
+    /// connection
+    pub keep_alive_secs: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            base_topic: "tamagochi".to_string(),
+            discovery_prefix: "homeassistant".to_string(),
+            keep_alive_secs: 60,
+            username: None,
+            password: None,
+        }
+    }
+}
+
+impl MqttConfig {
+    /// [`MqttConfig::keep_alive_secs`], at least a second, as 0 would turn
+    /// keep-alive off
+    pub fn keep_alive_secs(&self) -> u16 {
+        self.keep_alive_secs.max(1)
+    }
+}
+
+/// Commands run on events and actions
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HooksConfig {
+    pub hatched: Option<String>,
+    pub evolution: Option<String>,
+    pub death: Option<String>,
+    pub passed_away: Option<String>,
+    pub needs_attention: Option<String>,
+    pub birthday: Option<String>,
+    /// After an action was performed
+    pub action: Option<String>,
+    /// After an action wasn't available
+    pub action_unavailable: Option<String>,
+    /// Least time between two `needs_attention` hooks
+    pub needs_attention_minutes: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            hatched: None,
+            evolution: None,
+            death: None,
+            passed_away: None,
+            needs_attention: None,
+            birthday: None,
+            action: None,
+            action_unavailable: None,
+            needs_attention_minutes: 10,
+        }
+    }
+}
+
+impl Config {
+    /// Load `config.toml` from the data directory
+    pub fn load(dir: &Path) -> Result<Self, SlotError> {
+        match fs::read_to_string(dir.join("config.toml")) {
+            Ok(text) => toml::from_str(&text).map_err(|e| SlotError::Config(e.message().to_string())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// [`Config::max_catch_up_minutes`] in seconds
+    pub fn max_catch_up_secs(&self) -> u64 {
+        self.max_catch_up_minutes.saturating_mul(60)
+    }
+}
@@ -0,0 +1,516 @@
+//! Background daemon that keeps a pet alive in real time
+//!
+//! `tamagochi-cli daemon [name]` takes a slot, ticks its pet once a
+//! second and autosaves it. Other programs talk to it over the Unix socket
+//! `<data dir>/run/daemon.sock` with one JSON object per line:
+//!
+//! ```text
+//! → {"cmd":"feed"}
+//! ← {"time":"2024-05-01T12:30:05Z","tick":3612,"slot":"pet","type":"action","action":"Feed","performed":true}
+//! → {"cmd":"status"}
+//! ← {"type":"status","slot":"pet","generation":1,"alive":true,"stage":"Baby",...}
+//! → {"cmd":"subscribe"}
+//! ← {"type":"subscribed"}
+//! ← {"time":"2024-05-01T12:31:00Z","tick":3667,"slot":"pet","type":"event","event":"NeedsAttention"}
+//! ```
+//!
+//! Commands are `feed`, `play`, `medicine`, `clean`, `warm`, `status`,
+//! `subscribe` and `shutdown`. Actions are answered with the same record
+//! the event stream carries (see [`crate::events`]); after `subscribe` the
+//! connection also receives every event and action as it happens, which
+//! includes its own actions, so those aren't answered a second time.
+//! Errors are answered with `{"type":"error","message":"..."}`.
+//!
+//! `tamagochi-cli client <command>` sends one command and prints the
+//! replies. With `--http <port>` the daemon also serves the pet over HTTP
+//! (see [`crate::http`]).
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::SocketAddr;
+#[cfg(feature = "http")]
+use std::net::TcpStream;
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::core::{Action, GameSession};
+use crate::events::{EventLog, EventRecord, Recorder, StatusRecord};
+use crate::mqtt;
+use crate::oneshot;
+use crate::slots::{self, Slot, SlotError, SlotStore};
+
+/// Real time per game tick
+const TICK: Duration = Duration::from_secs(1);
+
+/// How often the pet is saved
+const AUTOSAVE: Duration = Duration::from_secs(30);
+
+/// Subscribers that can't take a line within this time are dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// A command sent to the daemon
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum Request {
+    Feed,
+    Play,
+    Medicine,
+    Clean,
+    Warm,
+    /// Reply with the pet's status
+    Status,
+    /// Receive every event and action from now on
+    Subscribe,
+    /// Save and stop the daemon
+    Shutdown,
+}
+
+impl Request {
+    /// All commands with their names, for the client
+    pub const ALL: [(&'static str, Request); 8] = [
+        ("feed", Request::Feed),
+        ("play", Request::Play),
+        ("medicine", Request::Medicine),
+        ("clean", Request::Clean),
+        ("warm", Request::Warm),
+        ("status", Request::Status),
+        ("subscribe", Request::Subscribe),
+        ("shutdown", Request::Shutdown),
+    ];
+
+    fn action(self) -> Option<Action> {
+        match self {
+            Request::Feed => Some(Action::Feed),
+            Request::Play => Some(Action::Play),
+            Request::Medicine => Some(Action::Medicine),
+            Request::Clean => Some(Action::Clean),
+            Request::Warm => Some(Action::Warm),
+            Request::Status | Request::Subscribe | Request::Shutdown => None,
+        }
+    }
+}
+
+/// Replies other than event records
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Reply<'a> {
+    Status(StatusRecord<'a>),
+    Subscribed,
+    Bye,
+    Error { message: String },
+}
+
+/// The socket of the daemon for a data directory
+///
+/// It lives in a private directory, so nobody else can connect while
+/// it's still open to everyone between binding and `chmod`.
+pub fn socket_path(dir: &Path) -> PathBuf {
+    dir.join("run").join("daemon.sock")
+}
+
+/// State shared by the ticker and the connections
+pub struct Daemon {
+    store: SlotStore,
+    state: Mutex<State>,
+    running: AtomicBool,
+}
+
+struct State {
+    slot: Slot,
+    log: EventLog,
+    subscribers: Vec<Subscriber>,
+}
+
+/// A connection that receives every event and action
+pub enum Subscriber {
+    /// A socket client after `subscribe`, one JSON line per record
+    Socket(UnixStream),
+    /// An HTTP client on `GET /events`, one server-sent event per record
+    #[cfg(feature = "http")]
+    Http(TcpStream),
+}
+
+impl Subscriber {
+    /// Tell the client the subscription started
+    fn acknowledge(&mut self) -> io::Result<()> {
+        match self {
+            Subscriber::Socket(stream) => write_line(stream, &to_line(&Reply::Subscribed)),
+            // A comment, so clients see the stream is open before the first event
+            #[cfg(feature = "http")]
+            Subscriber::Http(stream) => stream.write_all(b": subscribed\n\n"),
+        }
+    }
+
+    /// Send a record, false if the connection is gone
+    fn send(&mut self, line: &str) -> bool {
+        match self {
+            Subscriber::Socket(stream) => write_line(stream, line).is_ok(),
+            #[cfg(feature = "http")]
+            Subscriber::Http(stream) => crate::http::send_event(stream, line).is_ok(),
+        }
+    }
+}
+
+/// Send a line in one write, so lines from other threads can't split it
+fn write_line(stream: &mut UnixStream, line: &str) -> io::Result<()> {
+    stream.write_all(format!("{}\n", line).as_bytes())
+}
+
+/// What the daemon serves besides its socket
+#[derive(Debug, Default, PartialEq)]
+pub struct Frontends {
+    /// Serve the HTTP API on this address
+    pub http: Option<SocketAddr>,
+    /// Bridge to the MQTT broker at this `host:port`
+    pub mqtt: Option<String>,
+}
+
+/// `daemon [name] [--http <addr>]`, `web` and `mqtt`: run until a
+/// `shutdown` command
+pub fn run(
+    store: SlotStore,
+    config: &Config,
+    mut log: EventLog,
+    name: Option<&str>,
+    frontends: Frontends,
+) -> Result<(), SlotError> {
+    let (_lock, slot) = oneshot::open(&store, config, &mut log, name)?;
+    let path = socket_path(store.dir());
+    let listener = bind(&path)?;
+    println!("🕰️  Looking after '{}', listening on {}", slot.name, path.display());
+
+    let daemon = Daemon::new(store, slot, log);
+    if let Some(addr) = frontends.http {
+        serve_http(&daemon, addr)?;
+    }
+    let bridge = frontends.mqtt.map(|broker| {
+        let daemon = Arc::clone(&daemon);
+        let config = config.mqtt.clone();
+        thread::spawn(move || mqtt::bridge(&daemon, &broker, &config))
+    });
+    let accepting = Arc::clone(&daemon);
+    thread::spawn(move || accept(accepting, listener));
+
+    let result = daemon.tick_until_shutdown();
+    let _ = fs::remove_file(&path);
+    // Let the bridge mark the pet offline
+    if let Some(bridge) = bridge {
+        let _ = bridge.join();
+    }
+    result
+}
+
+#[cfg(feature = "http")]
+fn serve_http(daemon: &Arc<Daemon>, addr: SocketAddr) -> Result<(), SlotError> {
+    let listener = std::net::TcpListener::bind(addr)?;
+    println!("🌐 Serving HTTP on http://{}/", listener.local_addr()?);
+    let daemon = Arc::clone(daemon);
+    thread::spawn(move || crate::http::serve(daemon, listener));
+    Ok(())
+}
+
+#[cfg(not(feature = "http"))]
+fn serve_http(_daemon: &Arc<Daemon>, _addr: SocketAddr) -> Result<(), SlotError> {
+    let message = "this tamagochi-cli was built without the 'http' feature";
+    Err(io::Error::new(io::ErrorKind::Unsupported, message).into())
+}
+
+/// Bind the socket, replacing a stale one left by a crashed daemon
+fn bind(path: &Path) -> Result<UnixListener, SlotError> {
+    if UnixStream::connect(path).is_ok() {
+        let message = format!("a daemon is already listening on {}", path.display());
+        return Err(io::Error::new(io::ErrorKind::AddrInUse, message).into());
+    }
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e.into()),
+        _ => {}
+    }
+    if let Some(run) = path.parent() {
+        match fs::DirBuilder::new().mode(0o700).create(run) {
+            Err(e) if e.kind() != io::ErrorKind::AlreadyExists => return Err(e.into()),
+            _ => fs::set_permissions(run, fs::Permissions::from_mode(0o700))?,
+        }
+    }
+    let listener = UnixListener::bind(path)?;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+    Ok(listener)
+}
+
+fn accept(daemon: Arc<Daemon>, listener: UnixListener) {
+    for stream in listener.incoming().flatten() {
+        let daemon = Arc::clone(&daemon);
+        thread::spawn(move || {
+            // A client hanging up is nothing to report
+            let _ = daemon.serve(stream);
+        });
+    }
+}
+
+impl Daemon {
+    pub fn new(store: SlotStore, slot: Slot, log: EventLog) -> Arc<Self> {
+        Arc::new(Daemon {
+            store,
+            state: Mutex::new(State {
+                slot,
+                log,
+                subscribers: Vec::new(),
+            }),
+            running: AtomicBool::new(true),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panicking connection thread leaves the pet itself intact
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn tick_until_shutdown(&self) -> Result<(), SlotError> {
+        let mut next = Instant::now();
+        let mut saved = Instant::now();
+        while self.is_running() {
+            next += TICK;
+            thread::sleep(next.saturating_duration_since(Instant::now()));
+            // Don't race through missed ticks after the process was stopped
+            if Instant::now() > next + TICK * 5 {
+                next = Instant::now();
+            }
+
+            let mut state = self.state();
+            state.play(|session| session.update());
+            if saved.elapsed() >= AUTOSAVE {
+                state.save(&self.store)?;
+                saved = Instant::now();
+            }
+        }
+        self.state().save(&self.store)
+    }
+
+    /// Answer the commands of one connection until it closes
+    fn serve(&self, stream: UnixStream) -> io::Result<()> {
+        let reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        let mut subscribed = false;
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let request = match serde_json::from_str::<Request>(&line) {
+                Ok(request) => request,
+                Err(e) => {
+                    let message = format!("bad request: {}", e);
+                    self.reply(&mut writer, subscribed, &to_line(&Reply::Error { message }))?;
+                    continue;
+                }
+            };
+
+            let reply = match request {
+                Request::Status => to_line(&Reply::Status(StatusRecord::new(&self.state().slot))),
+                Request::Subscribe => {
+                    let subscriber = writer.try_clone()?;
+                    subscriber.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                    self.subscribe(Subscriber::Socket(subscriber))?;
+                    subscribed = true;
+                    continue;
+                }
+                Request::Shutdown => {
+                    self.running.store(false, Ordering::Relaxed);
+                    to_line(&Reply::Bye)
+                }
+                request => {
+                    let record = self.perform(request.action().expect("action request"));
+                    if subscribed {
+                        // The subscription already delivered the record
+                        continue;
+                    }
+                    to_line(&record)
+                }
+            };
+            self.reply(&mut writer, subscribed, &reply)?;
+        }
+        Ok(())
+    }
+
+    /// Answer on a connection, whose subscription the ticker writes to
+    /// under the state lock
+    fn reply(&self, writer: &mut UnixStream, subscribed: bool, line: &str) -> io::Result<()> {
+        let _state = subscribed.then(|| self.state());
+        write_line(writer, line)
+    }
+
+    /// Try an action on the pet, returning the record sent to subscribers
+    pub fn perform(&self, action: Action) -> EventRecord {
+        let (_, record) = self.state().play(|session| session.perform(action));
+        record.expect("every action is recorded")
+    }
+
+    /// The pet's status as JSON
+    #[cfg(feature = "http")]
+    pub fn status(&self) -> String {
+        to_line(&StatusRecord::new(&self.state().slot))
+    }
+
+    /// A copy of the pet as it is now
+    pub fn pet(&self) -> crate::core::Tamagochi {
+        self.state().slot.pet
+    }
+
+    /// The name of the pet's slot
+    pub fn slot_name(&self) -> String {
+        self.state().slot.name.clone()
+    }
+
+    /// False once a `shutdown` command came in
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Tell `subscriber` it's subscribed, then send it every event and
+    /// action from now on
+    ///
+    /// Both happen under the lock, so the acknowledgement comes first and
+    /// nothing published after it is missed.
+    pub fn subscribe(&self, mut subscriber: Subscriber) -> io::Result<()> {
+        let mut state = self.state();
+        subscriber.acknowledge()?;
+        state.subscribers.push(subscriber);
+        Ok(())
+    }
+}
+
+impl State {
+    /// Run `f` on the pet, then send what it recorded to the subscribers
+    ///
+    /// Returns what `f` returned and the record, if there is one; a tick or
+    /// an action makes at most one.
+    fn play<T>(&mut self, f: impl FnOnce(&mut GameSession<Recorder>) -> T) -> (T, Option<EventRecord>) {
+        let State { slot, log, subscribers } = self;
+        let mut session = GameSession::new(slot.pet, Recorder::new(log, &slot.name));
+        let result = f(&mut session);
+        let (pet, mut recorder) = session.into_parts();
+        slot.pet = pet;
+        if let Err(e) = recorder.take_result() {
+            eprintln!("warning: can't write event: {}", e);
+        }
+        let record = recorder.take_last();
+        if let Some(record) = &record {
+            let line = to_line(record);
+            subscribers.retain_mut(|subscriber| subscriber.send(&line));
+        }
+        (result, record)
+    }
+
+    fn save(&mut self, store: &SlotStore) -> Result<(), SlotError> {
+        self.slot.meta.last_played = slots::now();
+        store.save(&mut self.slot)
+    }
+}
+
+fn to_line(value: &impl Serialize) -> String {
+    // Plain data without maps keyed by non-strings always serializes
+    serde_json::to_string(value).unwrap_or_default()
+}
+
+/// `client <command>`: send a command to the daemon and print the replies
+///
+/// Returns after the first reply, except for `subscribe`, which prints
+/// events until the daemon goes away.
+pub fn client(dir: &Path, request: Request) -> Result<i32, SlotError> {
+    let path = socket_path(dir);
+    let stream = UnixStream::connect(&path).map_err(|e| {
+        let message = format!("can't reach the daemon on {} ({})", path.display(), e);
+        io::Error::new(e.kind(), message)
+    })?;
+    let mut writer = stream.try_clone()?;
+    writeln!(writer, "{}", to_line(&request))?;
+
+    let mut status = 0;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        println!("{}", line);
+        let reply: serde_json::Value = serde_json::from_str(&line).unwrap_or_default();
+        if reply["type"] == "error" {
+            status = 1;
+        }
+        if request != Request::Subscribe {
+            break;
+        }
+    }
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn send(stream: &mut UnixStream, reader: &mut impl BufRead, request: &str) -> serde_json::Value {
+        writeln!(stream, "{}", request).unwrap();
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    #[test]
+    fn test_protocol() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-daemon-{}", std::process::id()));
+        let store = SlotStore::at(dir.clone()).unwrap();
+        store.create("pet").unwrap();
+        let daemon = thread::spawn({
+            let dir = dir.clone();
+            move || {
+                let store = SlotStore::at(dir).unwrap();
+                run(store, &Config::default(), EventLog::open(None).unwrap(), Some("pet"), Frontends::default())
+            }
+        });
+
+        let path = socket_path(&dir);
+        let mut stream = loop {
+            match UnixStream::connect(&path) {
+                Ok(stream) => break stream,
+                Err(_) => thread::sleep(Duration::from_millis(10)),
+            }
+        };
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let run = fs::metadata(path.parent().unwrap()).unwrap();
+        assert_eq!(run.permissions().mode() & 0o777, 0o700);
+
+        let status = send(&mut stream, &mut reader, r#"{"cmd":"status"}"#);
+        assert_eq!(status["type"], "status");
+        assert_eq!(status["stage"], "Egg");
+
+        let reply = send(&mut stream, &mut reader, r#"{"cmd":"feed"}"#);
+        assert_eq!(reply["type"], "action");
+        assert_eq!(reply["performed"], false);
+        let reply = send(&mut stream, &mut reader, r#"{"cmd":"warm"}"#);
+        assert_eq!(reply["performed"], true);
+
+        let reply = send(&mut stream, &mut reader, r#"{"cmd":"dance"}"#);
+        assert_eq!(reply["type"], "error");
+
+        assert_eq!(send(&mut stream, &mut reader, r#"{"cmd":"subscribe"}"#)["type"], "subscribed");
+        // A subscriber's own action arrives once, through the subscription
+        assert_eq!(send(&mut stream, &mut reader, r#"{"cmd":"warm"}"#)["action"], "Warm");
+        assert_eq!(send(&mut stream, &mut reader, r#"{"cmd":"status"}"#)["type"], "status");
+        let mut watcher = UnixStream::connect(&path).unwrap();
+        let mut watcher_reader = BufReader::new(watcher.try_clone().unwrap());
+        send(&mut watcher, &mut watcher_reader, r#"{"cmd":"clean"}"#);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        let event: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(event["action"], "Clean");
+
+        assert_eq!(send(&mut watcher, &mut watcher_reader, r#"{"cmd":"shutdown"}"#)["type"], "bye");
+        daemon.join().unwrap().unwrap();
+        assert!(!path.exists());
+        // The warmth was saved on shutdown
+        assert!(store.load("pet").unwrap().pet.care_quality > 50);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
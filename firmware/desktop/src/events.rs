@@ -0,0 +1,277 @@
+//! Event stream for consumers outside the game
+//!
+//! With `--events-out <path>`, every game event and player action is
+//! appended to the file as one JSON object per line (NDJSON), e.g.
+//!
+//! ```json
+//! {"time":"2024-05-01T12:30:05Z","tick":3612,"slot":"pet","type":"action","action":"Feed","performed":true}
+//! {"time":"2024-05-01T12:30:06Z","tick":3613,"slot":"pet","type":"event","event":{"Evolution":"Child"}}
+//! ```
+//!
+//! `tick` is the pet's age in ticks when it happened. Events replayed for
+//! the time the game was closed carry the time they would have happened,
+//! not the time they were replayed. The format is described by
+//! `tamagochi-cli schema events`.
+
+use schemars::generate::SchemaSettings;
+use schemars::{JsonSchema, Schema};
+use serde::{Deserialize, Serialize};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::mem;
+use std::path::Path;
+
+use crate::core::{Action, GameEvent, Listener, NeedsStatus, StatusSummary, Tamagochi};
+use crate::hooks::Hooks;
+use crate::slots::{self, Slot};
+
+/// One line of the event stream
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct EventRecord {
+    /// Wall-clock time it happened (RFC 3339, UTC)
+    pub time: String,
+    /// The pet's age in ticks
+    pub tick: u32,
+    /// The save slot of the pet
+    pub slot: String,
+    #[serde(flatten)]
+    pub entry: Entry,
+}
+
+impl EventRecord {
+    /// A record of something that just happened to `pet`
+    pub fn new(slot: &str, pet: &Tamagochi, entry: Entry) -> Self {
+        Self::at(slots::now(), slot, pet, entry)
+    }
+
+    /// A record of something that happened to `pet` at `time` (Unix
+    /// seconds)
+    pub fn at(time: u64, slot: &str, pet: &Tamagochi, entry: Entry) -> Self {
+        Self {
+            time: slots::format_rfc3339(time),
+            tick: pet.age,
+            slot: slot.to_string(),
+            entry,
+        }
+    }
+}
+
+/// What happened
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Entry {
+    /// Something happened to the pet
+    Event { event: GameEvent },
+    /// The player tried an action; `performed` is false if it wasn't
+    /// available
+    Action { action: Action, performed: bool },
+}
+
+/// Appends records to the `--events-out` file, if there is one, and runs
+/// their hooks
+pub struct EventLog {
+    file: Option<File>,
+    hooks: Hooks,
+}
+
+impl EventLog {
+    /// Append to `path`, or record nothing without a path
+    pub fn open(path: Option<&Path>) -> io::Result<Self> {
+        let file = match path {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+        Ok(Self {
+            file,
+            hooks: Hooks::default(),
+        })
+    }
+
+    /// Also run `hooks` for every record
+    pub fn with_hooks(self, hooks: Hooks) -> Self {
+        Self { hooks, ..self }
+    }
+
+    /// Run the hook of a record and append it; `pet` is the pet right
+    /// after it happened
+    pub fn write(&mut self, record: &EventRecord, pet: &Tamagochi) -> io::Result<()> {
+        self.hooks.run(record, pet);
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_vec(record).map_err(io::Error::other)?;
+        line.push(b'\n');
+        // One write per line, so readers tailing the file never see half a record
+        file.write_all(&line)
+    }
+}
+
+/// Records what happens in a [`GameSession`](crate::core::GameSession) to
+/// an [`EventLog`]
+pub struct Recorder<'a> {
+    log: &'a mut EventLog,
+    slot: String,
+    /// For replayed ticks: the wall time they started at and the pet's age
+    /// then
+    replay: Option<(u64, u32)>,
+    /// The last record since `take_last`
+    last: Option<EventRecord>,
+    /// The first failure since `take_result`
+    logged: io::Result<()>,
+}
+
+impl<'a> Recorder<'a> {
+    /// Record what happens to the pet of `slot` as it happens
+    pub fn new(log: &'a mut EventLog, slot: &str) -> Self {
+        Self {
+            log,
+            slot: slot.to_string(),
+            replay: None,
+            last: None,
+            logged: Ok(()),
+        }
+    }
+
+    /// Stamp events with when they would have happened, for ticks replayed
+    /// from wall time `start` (Unix seconds) on, when the pet was `age`
+    /// ticks old; a tick is a second
+    pub fn replaying(self, start: u64, age: u32) -> Self {
+        Self {
+            replay: Some((start, age)),
+            ..self
+        }
+    }
+
+    pub fn take_result(&mut self) -> io::Result<()> {
+        mem::replace(&mut self.logged, Ok(()))
+    }
+
+    /// The last record, to pass it on elsewhere
+    pub fn take_last(&mut self) -> Option<EventRecord> {
+        self.last.take()
+    }
+
+    fn record(&mut self, record: EventRecord, tama: &Tamagochi) {
+        if self.logged.is_ok() {
+            self.logged = self.log.write(&record, tama);
+        }
+        self.last = Some(record);
+    }
+}
+
+impl Listener for Recorder<'_> {
+    fn on_event(&mut self, event: GameEvent, tama: &Tamagochi) {
+        let time = match self.replay {
+            Some((start, age)) => start + u64::from(tama.age - age),
+            None => slots::now(),
+        };
+        self.record(EventRecord::at(time, &self.slot, tama, Entry::Event { event }), tama);
+    }
+
+    fn on_action(&mut self, action: Action, performed: bool, tama: &Tamagochi) {
+        self.record(EventRecord::new(&self.slot, tama, Entry::Action { action, performed }), tama);
+    }
+}
+
+/// A pet's status as JSON (`status --json`, the daemon's `status`)
+#[derive(Serialize)]
+pub struct StatusRecord<'a> {
+    pub slot: &'a str,
+    pub generation: u16,
+    #[serde(flatten)]
+    pub summary: StatusSummary,
+    pub needs: NeedsStatus,
+    pub modified: bool,
+}
+
+impl<'a> StatusRecord<'a> {
+    pub fn new(slot: &'a Slot) -> Self {
+        Self {
+            slot: &slot.name,
+            generation: slot.pet.generation,
+            summary: slot.pet.status_summary(),
+            needs: slot.pet.needs_status(),
+            modified: slot.meta.modified,
+        }
+    }
+}
+
+/// JSON Schema of a line in the event stream
+pub fn schema() -> Schema {
+    SchemaSettings::default()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<EventRecord>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::{GameSession, LifeStage};
+    use std::fs;
+
+    #[test]
+    fn test_records_are_flat_json_lines() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-events-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("events.ndjson");
+        let _ = fs::remove_file(&path);
+
+        let mut pet = Tamagochi::new();
+        pet.age = 3612;
+        let mut log = EventLog::open(Some(&path)).unwrap();
+        let feed = Entry::Action { action: Action::Feed, performed: true };
+        log.write(&EventRecord::new("pet", &pet, feed), &pet).unwrap();
+        // 2024-05-01T12:30:06Z
+        let evolution = Entry::Event { event: GameEvent::Evolution(LifeStage::Child) };
+        log.write(&EventRecord::at(1714566606, "pet", &pet, evolution), &pet).unwrap();
+        drop(log);
+
+        let text = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(text.ends_with('\n'));
+
+        let action: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(action["type"], "action");
+        assert_eq!(action["action"], "Feed");
+        assert_eq!(action["performed"], true);
+        assert_eq!(action["tick"], 3612);
+        assert_eq!(action["slot"], "pet");
+
+        assert_eq!(
+            lines[1],
+            r#"{"time":"2024-05-01T12:30:06Z","tick":3612,"slot":"pet","type":"event","event":{"Evolution":"Child"}}"#
+        );
+
+        // Consumers in Rust read them back with the same type
+        for line in lines {
+            let record: EventRecord = serde_json::from_str(line).unwrap();
+            assert_eq!(serde_json::to_string(&record).unwrap(), line);
+        }
+        let record: EventRecord = serde_json::from_str(text.lines().nth(1).unwrap()).unwrap();
+        assert!(matches!(record.entry, Entry::Event { event: GameEvent::Evolution(LifeStage::Child) }));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_replayed_events_are_stamped_when_they_happened() {
+        let mut log = EventLog::open(None).unwrap();
+        let mut pet = Tamagochi::new();
+        pet.stage = LifeStage::Baby;
+        pet.age = 500;
+        pet.hunger = 78;
+        // 2024-05-01T12:00:00Z
+        let recorder = Recorder::new(&mut log, "pet").replaying(1714564800, pet.age);
+        let mut session = GameSession::new(pet, recorder);
+
+        session.advance(2);
+        let record = session.listener_mut().take_last().unwrap();
+        assert!(matches!(record.entry, Entry::Event { event: GameEvent::NeedsAttention }));
+        assert_eq!(record.tick, 502);
+        assert_eq!(record.time, "2024-05-01T12:00:02Z");
+        assert!(session.listener_mut().take_last().is_none());
+        session.listener_mut().take_result().unwrap();
+    }
+}
@@ -0,0 +1,267 @@
+//! Shell commands run when something happens to the pet
+//!
+//! The `[hooks]` section of config.toml maps events and action outcomes to
+//! commands:
+//!
+//! ```toml
+//! [hooks]
+//! evolution = 'notify-send Tamagochi "Evolved into $TAMAGOCHI_STAGE"'
+//! needs_attention = 'notify-send Tamagochi "Hunger $TAMAGOCHI_HUNGER, health $TAMAGOCHI_HEALTH"'
+//! death = 'jq -r .time >> ~/tamagochi-graveyard.txt'
+//! # needs_attention comes every tick while a need is critical; run its
+//! # hook at most this often per pet, also across one-shot commands
+//! needs_attention_minutes = 10
+//! ```
+//!
+//! Keys are `hatched`, `evolution`, `death`, `passed_away`,
+//! `needs_attention`, `birthday`, `action` (performed) and
+//! `action_unavailable`. Commands run with `sh -c` in the background,
+//! whatever mode the CLI is in, with the event record (see
+//! [`crate::events`]) as JSON on stdin and these environment variables:
+//!
+//! | Variable                 | Content                                        |
+//! |--------------------------|------------------------------------------------|
+//! | `TAMAGOCHI_EVENT`        | The hook's key, e.g. `evolution`               |
+//! | `TAMAGOCHI_SLOT`         | Slot name                                      |
+//! | `TAMAGOCHI_TIME`         | When it happened (RFC 3339)                    |
+//! | `TAMAGOCHI_TICK`         | The pet's age in ticks                         |
+//! | `TAMAGOCHI_STAGE`        | Stage after the event, e.g. `Premium adult`    |
+//! | `TAMAGOCHI_AGE_DAYS`     | Age in days                                    |
+//! | `TAMAGOCHI_HUNGER`, `TAMAGOCHI_HAPPINESS`, `TAMAGOCHI_HEALTH` | Levels, 0 to 100 |
+//! | `TAMAGOCHI_ACTION`       | `feed`, `play`, ... (action hooks only)        |
+//!
+//! A hook's output is discarded except for stderr. When `needs_attention`
+//! last ran for a slot is kept in `<data dir>/hooks/`.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+
+use crate::config::HooksConfig;
+use crate::core::{Action, GameEvent, Tamagochi};
+use crate::events::{Entry, EventRecord};
+use crate::prompt;
+use crate::slots;
+
+/// Runs the configured hooks
+#[derive(Debug, Default)]
+pub struct Hooks {
+    config: HooksConfig,
+    /// Where the time `needs_attention` last ran is kept
+    dir: PathBuf,
+    /// Hooks that may still be running, to reap them
+    running: Vec<Child>,
+}
+
+impl Hooks {
+    /// Hooks of `config`, throttled with times kept in the data directory
+    /// `dir`
+    pub fn new(config: HooksConfig, dir: &Path) -> Self {
+        Self {
+            config,
+            dir: dir.join("hooks"),
+            running: Vec::new(),
+        }
+    }
+
+    /// Start the hook for a record, if there is one; `pet` is the pet
+    /// right after it happened
+    pub fn run(&mut self, record: &EventRecord, pet: &Tamagochi) {
+        self.running.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+
+        let key = hook_key(&record.entry);
+        let Some(script) = self.command(key).cloned() else {
+            return;
+        };
+        if key == "needs_attention" && !self.needs_attention_due(&record.slot) {
+            return;
+        }
+
+        let summary = pet.status_summary();
+        let mut command = Command::new("sh");
+        command
+            .arg("-c")
+            .arg(script)
+            .env("TAMAGOCHI_EVENT", key)
+            .env("TAMAGOCHI_SLOT", &record.slot)
+            .env("TAMAGOCHI_TIME", &record.time)
+            .env("TAMAGOCHI_TICK", record.tick.to_string())
+            .env("TAMAGOCHI_STAGE", prompt::stage_name(summary.stage))
+            .env("TAMAGOCHI_AGE_DAYS", summary.age_days.to_string())
+            .env("TAMAGOCHI_HUNGER", summary.hunger_level.to_string())
+            .env("TAMAGOCHI_HAPPINESS", summary.happiness_level.to_string())
+            .env("TAMAGOCHI_HEALTH", summary.health_level.to_string())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null());
+        if let Entry::Action { action, .. } = record.entry {
+            command.env("TAMAGOCHI_ACTION", action_name(action));
+        }
+
+        match command.spawn() {
+            Ok(mut child) => {
+                if let Some(mut stdin) = child.stdin.take() {
+                    // A hook that doesn't read its input may be gone already
+                    let json = serde_json::to_string(record).unwrap_or_default();
+                    let _ = writeln!(stdin, "{}", json);
+                }
+                self.running.push(child);
+            }
+            Err(e) => eprintln!("warning: can't run the {} hook: {}", key, e),
+        }
+    }
+
+    /// Whether the `needs_attention` hook of `slot` may run again; if so,
+    /// notes that it runs now
+    ///
+    /// The time is kept on disk, as one-shot commands from a shell prompt
+    /// or cron each start a new process.
+    fn needs_attention_due(&self, slot: &str) -> bool {
+        let path = self.dir.join(format!("{}.needs_attention", slot));
+        let interval = self.config.needs_attention_minutes.saturating_mul(60);
+        let now = slots::now();
+        let last = fs::read_to_string(&path).ok().and_then(|text| text.trim().parse::<u64>().ok());
+        // A clock that went back doesn't hold the hook off until it catches up
+        if last.is_some_and(|last| last <= now && now - last < interval) {
+            return false;
+        }
+        if let Err(e) = note_time(&path, now) {
+            eprintln!("warning: can't note when the needs_attention hook ran: {}", e);
+        }
+        true
+    }
+
+    fn command(&self, key: &str) -> Option<&String> {
+        let hooks = &self.config;
+        match key {
+            "hatched" => hooks.hatched.as_ref(),
+            "evolution" => hooks.evolution.as_ref(),
+            "death" => hooks.death.as_ref(),
+            "passed_away" => hooks.passed_away.as_ref(),
+            "needs_attention" => hooks.needs_attention.as_ref(),
+            "birthday" => hooks.birthday.as_ref(),
+            "action" => hooks.action.as_ref(),
+            "action_unavailable" => hooks.action_unavailable.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+fn note_time(path: &Path, secs: u64) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, secs.to_string())
+}
+
+/// The config key of the hook for an entry
+fn hook_key(entry: &Entry) -> &'static str {
+    match entry {
+        Entry::Event { event } => match event {
+            GameEvent::Hatched => "hatched",
+            GameEvent::Evolution(_) => "evolution",
+            GameEvent::Death => "death",
+            GameEvent::PassedAway => "passed_away",
+            GameEvent::NeedsAttention => "needs_attention",
+            GameEvent::Birthday(_) => "birthday",
+        },
+        Entry::Action { performed: true, .. } => "action",
+        Entry::Action { performed: false, .. } => "action_unavailable",
+    }
+}
+
+fn action_name(action: Action) -> &'static str {
+    match action {
+        Action::Feed => "feed",
+        Action::Play => "play",
+        Action::Medicine => "medicine",
+        Action::Clean => "clean",
+        Action::Warm => "warm",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_hooks_get_the_event_and_are_throttled() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-hooks-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("out");
+        let config = HooksConfig {
+            needs_attention: Some(format!("cat >> {0}; echo $TAMAGOCHI_EVENT $TAMAGOCHI_HEALTH >> {0}", out.display())),
+            action: Some(format!("echo $TAMAGOCHI_ACTION >> {}", out.display())),
+            ..HooksConfig::default()
+        };
+        let mut hooks = Hooks::new(config, &dir);
+        let mut pet = Tamagochi::new();
+        pet.health = 5;
+
+        let needs = EventRecord::new("pet", &pet, Entry::Event { event: GameEvent::NeedsAttention });
+        for _ in 0..3 {
+            hooks.run(&needs, &pet);
+        }
+        let unavailable = Entry::Action { action: Action::Feed, performed: false };
+        hooks.run(&EventRecord::new("pet", &pet, unavailable), &pet);
+        for child in &mut hooks.running {
+            child.wait().unwrap();
+        }
+        let feed = Entry::Action { action: Action::Feed, performed: true };
+        hooks.run(&EventRecord::new("pet", &pet, feed), &pet);
+        for child in &mut hooks.running {
+            child.wait().unwrap();
+        }
+
+        let out = fs::read_to_string(out).unwrap();
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3, "{}", out);
+        let json: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(json["event"], "NeedsAttention");
+        assert_eq!(lines[1], "needs_attention 5");
+        assert_eq!(lines[2], "feed");
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_needs_attention_throttle_outlives_the_process() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-hooks-throttle-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let out = dir.join("out");
+        let config = HooksConfig {
+            needs_attention: Some(format!("echo $TAMAGOCHI_SLOT >> {}", out.display())),
+            ..HooksConfig::default()
+        };
+        let pet = Tamagochi::new();
+        let needs = |slot| EventRecord::new(slot, &pet, Entry::Event { event: GameEvent::NeedsAttention });
+
+        // Two one-shot runs, each with hooks of its own
+        for _ in 0..2 {
+            let mut hooks = Hooks::new(config.clone(), &dir);
+            hooks.run(&needs("pet"), &pet);
+            hooks.run(&needs("other"), &pet);
+            for child in &mut hooks.running {
+                child.wait().unwrap();
+            }
+        }
+        let mut ran: Vec<String> = fs::read_to_string(&out).unwrap().lines().map(String::from).collect();
+        ran.sort();
+        assert_eq!(ran, ["other", "pet"]);
+
+        // Without a throttle, every run gets it
+        let config = HooksConfig {
+            needs_attention_minutes: 0,
+            ..config
+        };
+        let mut hooks = Hooks::new(config, &dir);
+        hooks.run(&needs("pet"), &pet);
+        for child in &mut hooks.running {
+            child.wait().unwrap();
+        }
+        assert_eq!(fs::read_to_string(&out).unwrap().lines().count(), 3);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,412 @@
+//! HTTP API for dashboards and wall displays
+//!
+//! `tamagochi-cli daemon --http <port>` serves the daemon's pet over
+//! HTTP/1.1 as well, on 127.0.0.1 unless a full address is given:
+//!
+//! | Request                 | Response                                         |
+//! |-------------------------|--------------------------------------------------|
+//! | `GET /`                 | A page to play in the browser (`web` command)    |
+//! | `GET /status`           | The status as JSON, like `status --json`         |
+//! | `POST /actions/<action>`| The action's event record; `409` if unavailable  |
+//! | `GET /events`           | Server-sent events, one per event record         |
+//! | `GET /screen.png`       | The current frame, `?scale=1..16` (default 4)    |
+//!
+//! Actions are `feed`, `play`, `medicine`, `clean` and `warm`. The JSON
+//! is the same as on the daemon's socket (see [`crate::events`]); errors
+//! are `{"type":"error","message":"..."}`. Every request gets its own
+//! connection (`Connection: close`), except `/events`, which stays open.
+//!
+//! So that web pages elsewhere can't play with the pet, requests are
+//! refused with `403` if their `Host` is anything but localhost or the
+//! address they came in on, or if they come from a page of another
+//! origin.
+
+use png::{BitDepth, ColorType, Encoder};
+use serde_json::json;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use tamagochi_graphics::{screen, Framebuffer};
+
+use crate::core::{Action, Tamagochi};
+use crate::daemon::{Daemon, Subscriber};
+
+/// Clients must send their request within this time
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Event streams that can't take an event within this time are dropped
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The browser frontend; it only uses the endpoints below
+const INDEX_HTML: &str = include_str!("../web/index.html");
+
+/// Longest request head accepted
+const MAX_HEAD: usize = 8 * 1024;
+
+/// Answer requests until the listener fails
+pub fn serve(daemon: Arc<Daemon>, listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        let daemon = Arc::clone(&daemon);
+        thread::spawn(move || {
+            // A client hanging up is nothing to report
+            let _ = handle(&daemon, stream);
+        });
+    }
+}
+
+/// Send one record to an event stream
+pub fn send_event(stream: &mut TcpStream, line: &str) -> io::Result<()> {
+    write!(stream, "data: {}\n\n", line)?;
+    stream.flush()
+}
+
+/// A parsed request line and the headers that matter
+struct Request {
+    method: String,
+    path: String,
+    query: String,
+    host: Option<String>,
+    origin: Option<String>,
+}
+
+impl Request {
+    /// Whether the request is addressed to this server, from a page of
+    /// its own if from a browser at all
+    ///
+    /// A page on another site can still make the browser send requests
+    /// here, with its own `Origin`, or with its own name in `Host` after
+    /// pointing that name at this machine (DNS rebinding).
+    fn is_local(&self, local: SocketAddr) -> bool {
+        let host_ok = self.host.as_deref().is_none_or(|host| is_own_authority(host, local));
+        let origin_ok = self.origin.as_deref().is_none_or(|origin| {
+            origin
+                .strip_prefix("http://")
+                .is_some_and(|authority| is_own_authority(authority, local))
+        });
+        host_ok && origin_ok
+    }
+}
+
+/// Whether `authority` (`host[:port]`) names this server on `local`
+fn is_own_authority(authority: &str, local: SocketAddr) -> bool {
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if !port.contains(']') => (host, port.parse::<u16>().ok()),
+        _ => (authority, Some(80)),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let host_ok = host.eq_ignore_ascii_case("localhost")
+        || host
+            .parse::<IpAddr>()
+            .is_ok_and(|ip| ip == local.ip() || ip.is_loopback());
+    host_ok && port == Some(local.port())
+}
+
+fn handle(daemon: &Daemon, mut stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+    let request = match read_request(&stream) {
+        Ok(request) => request,
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            return respond_error(&mut stream, "400 Bad Request", &e.to_string());
+        }
+        Err(e) => return Err(e),
+    };
+    if !request.is_local(stream.local_addr()?) {
+        return respond_error(&mut stream, "403 Forbidden", "only pages served from here may use this server");
+    }
+
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/") => respond(&mut stream, "200 OK", "text/html; charset=utf-8", INDEX_HTML.as_bytes()),
+        ("GET", "/status") => respond(&mut stream, "200 OK", "application/json", daemon.status().as_bytes()),
+        ("POST", path) if path.starts_with("/actions/") => {
+            let Some(action) = parse_action(&path["/actions/".len()..]) else {
+                return respond_error(&mut stream, "404 Not Found", &format!("no such action: {}", path));
+            };
+            let record = daemon.perform(action);
+            let performed = matches!(record.entry, crate::events::Entry::Action { performed: true, .. });
+            let status = if performed { "200 OK" } else { "409 Conflict" };
+            let body = serde_json::to_vec(&record).map_err(io::Error::other)?;
+            respond(&mut stream, status, "application/json", &body)
+        }
+        ("GET", "/events") => {
+            stream.write_all(
+                b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-store\r\n\r\n",
+            )?;
+            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+            daemon.subscribe(Subscriber::Http(stream))
+        }
+        ("GET", "/screen.png") => {
+            let scale = match query_param(&request.query, "scale") {
+                None => 4,
+                Some(value) => match value.parse::<u32>() {
+                    Ok(scale @ 1..=16) => scale,
+                    _ => return respond_error(&mut stream, "400 Bad Request", "scale must be 1 to 16"),
+                },
+            };
+            let png = screen_png(&daemon.pet(), scale)?;
+            respond(&mut stream, "200 OK", "image/png", &png)
+        }
+        (_, "/" | "/status" | "/events" | "/screen.png") => {
+            respond_error(&mut stream, "405 Method Not Allowed", "use GET")
+        }
+        (_, path) if path.starts_with("/actions/") => {
+            respond_error(&mut stream, "405 Method Not Allowed", "use POST")
+        }
+        (_, path) => respond_error(&mut stream, "404 Not Found", &format!("no such resource: {}", path)),
+    }
+}
+
+/// Read the request line, skipping the headers and any body
+///
+/// No endpoint takes a body, but one sent anyway is read, so closing the
+/// connection doesn't reset it before the client has the response.
+fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    let bad = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut reader = BufReader::new(stream.take(MAX_HEAD as u64));
+
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target), Some(version)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(bad("malformed request line"));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(bad("only HTTP/1.x is supported"));
+    }
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let mut request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        host: None,
+        origin: None,
+    };
+
+    let mut body_len = 0;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 {
+            return Err(bad("request head too long or cut off"));
+        }
+        if header.trim_end().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            let value = value.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                body_len = value.parse().map_err(|_| bad("invalid Content-Length"))?;
+            } else if name.eq_ignore_ascii_case("host") {
+                request.host = Some(value.to_string());
+            } else if name.eq_ignore_ascii_case("origin") {
+                request.origin = Some(value.to_string());
+            }
+        }
+    }
+    io::copy(&mut reader.take(body_len), &mut io::sink())?;
+    Ok(request)
+}
+
+fn parse_action(name: &str) -> Option<Action> {
+    match name {
+        "feed" => Some(Action::Feed),
+        "play" => Some(Action::Play),
+        "medicine" => Some(Action::Medicine),
+        "clean" => Some(Action::Clean),
+        "warm" => Some(Action::Warm),
+        _ => None,
+    }
+}
+
+fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+    query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, value)| value)
+}
+
+fn respond(stream: &mut TcpStream, status: &str, content_type: &str, body: &[u8]) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(body)?;
+    stream.flush()
+}
+
+fn respond_error(stream: &mut TcpStream, status: &str, message: &str) -> io::Result<()> {
+    let body = json!({ "type": "error", "message": message }).to_string();
+    respond(stream, status, "application/json", body.as_bytes())
+}
+
+/// The main game screen as a black and white PNG, `scale` pixels per dot
+pub fn screen_png(tama: &Tamagochi, scale: u32) -> io::Result<Vec<u8>> {
+    let mut frame = Framebuffer::new();
+    // The daemon ticks once a second, so animate one frame per tick
+    screen::draw_game_state(&mut frame, tama, tama.age as u8);
+
+    let width = Framebuffer::WIDTH * scale;
+    let height = Framebuffer::HEIGHT * scale;
+    let row_bytes = width.div_ceil(8) as usize;
+    let mut data = vec![0u8; row_bytes * height as usize];
+    for y in 0..height {
+        for x in 0..width {
+            if frame.pixel(x / scale, y / scale) {
+                data[y as usize * row_bytes + x as usize / 8] |= 0x80 >> (x % 8);
+            }
+        }
+    }
+
+    let mut png = Vec::new();
+    let mut encoder = Encoder::new(&mut png, width, height);
+    encoder.set_color(ColorType::Grayscale);
+    encoder.set_depth(BitDepth::One);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(&data).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)?;
+    Ok(png)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::LifeStage;
+    use crate::events::EventLog;
+    use crate::slots::SlotStore;
+    use std::net::SocketAddr;
+
+    /// Send a request without a body and return the status code and body
+    fn request(addr: SocketAddr, method: &str, target: &str) -> (u16, Vec<u8>) {
+        send(addr, &format!("{} {} HTTP/1.1\r\nHost: localhost:{}\r\n\r\n", method, target, addr.port()))
+    }
+
+    /// Send a raw request and return the status code and body
+    fn send(addr: SocketAddr, raw: &str) -> (u16, Vec<u8>) {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        stream.write_all(raw.as_bytes()).unwrap();
+        let mut response = Vec::new();
+        stream.read_to_end(&mut response).unwrap();
+        let head_end = response.windows(4).position(|w| w == b"\r\n\r\n").unwrap();
+        let head = String::from_utf8_lossy(&response[..head_end]).into_owned();
+        let status = head[9..12].parse().unwrap();
+        (status, response[head_end + 4..].to_vec())
+    }
+
+    fn json(body: &[u8]) -> serde_json::Value {
+        serde_json::from_slice(body).unwrap()
+    }
+
+    #[test]
+    fn test_api() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-http-{}", std::process::id()));
+        let store = SlotStore::at(dir.clone()).unwrap();
+        store.create("pet").unwrap();
+        let mut slot = store.load("pet").unwrap();
+        slot.pet.stage = LifeStage::Baby;
+        slot.pet.hunger = 80;
+        let daemon = Daemon::new(store, slot, EventLog::open(None).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(daemon, listener));
+
+        let (status, body) = request(addr, "GET", "/");
+        assert_eq!(status, 200);
+        assert!(String::from_utf8(body).unwrap().contains("<canvas"));
+
+        let (status, body) = request(addr, "GET", "/status");
+        assert_eq!(status, 200);
+        assert_eq!(json(&body)["stage"], "Baby");
+
+        let mut events = TcpStream::connect(addr).unwrap();
+        write!(events, "GET /events HTTP/1.1\r\n\r\n").unwrap();
+        let mut events = BufReader::new(events);
+        let mut line = String::new();
+        while line != ": subscribed\n" {
+            line.clear();
+            events.read_line(&mut line).unwrap();
+        }
+
+        let (status, body) = send(addr, "POST /actions/feed HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}");
+        assert_eq!(status, 200);
+        assert_eq!(json(&body)["performed"], true);
+        let (status, body) = request(addr, "POST", "/actions/warm");
+        assert_eq!(status, 409);
+        assert_eq!(json(&body)["performed"], false);
+
+        line.clear();
+        events.read_line(&mut line).unwrap(); // the blank line after the comment
+        line.clear();
+        events.read_line(&mut line).unwrap();
+        let event = json(line.strip_prefix("data: ").unwrap().as_bytes());
+        assert_eq!(event["action"], "Feed");
+
+        assert_eq!(request(addr, "POST", "/actions/dance").0, 404);
+        assert_eq!(request(addr, "GET", "/actions/feed").0, 405);
+        assert_eq!(request(addr, "GET", "/nope").0, 404);
+        assert_eq!(request(addr, "GET", "/screen.png?scale=0").0, 400);
+
+        let (status, body) = request(addr, "GET", "/screen.png?scale=2");
+        assert_eq!(status, 200);
+        let decoder = png::Decoder::new(body.as_slice());
+        let reader = decoder.read_info().unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (256, 128));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_only_local_pages_are_served() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-http-local-{}", std::process::id()));
+        let store = SlotStore::at(dir.clone()).unwrap();
+        let slot = store.create("pet").unwrap();
+        let daemon = Daemon::new(store, slot, EventLog::open(None).unwrap());
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(daemon, listener));
+
+        let warm = |headers: &str| send(addr, &format!("POST /actions/warm HTTP/1.1\r\n{}\r\n", headers)).0;
+        let port = addr.port();
+        assert_eq!(warm(""), 200);
+        assert_eq!(warm(&format!("Host: 127.0.0.1:{}\r\n", port)), 200);
+        assert_eq!(warm(&format!("Host: localhost:{0}\r\nOrigin: http://localhost:{0}\r\n", port)), 200);
+
+        // Another site's page, directly or through a rebound name
+        assert_eq!(warm(&format!("Host: localhost:{}\r\nOrigin: https://evil.example\r\n", port)), 403);
+        assert_eq!(warm(&format!("Host: localhost:{}\r\nOrigin: null\r\n", port)), 403);
+        assert_eq!(warm(&format!("Host: evil.example:{}\r\n", port)), 403);
+        assert_eq!(warm("Host: localhost\r\n"), 403);
+        assert_eq!(request(addr, "GET", "/status").0, 200);
+        let (status, body) = send(addr, "GET /status HTTP/1.1\r\nHost: evil.example\r\n\r\n");
+        assert_eq!(status, 403);
+        assert_eq!(json(&body)["type"], "error");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_own_authorities() {
+        let local: SocketAddr = "192.168.1.5:8080".parse().unwrap();
+        assert!(is_own_authority("localhost:8080", local));
+        assert!(is_own_authority("192.168.1.5:8080", local));
+        assert!(is_own_authority("[::1]:8080", local));
+        assert!(!is_own_authority("192.168.1.6:8080", local));
+        assert!(!is_own_authority("localhost:8081", local));
+        assert!(!is_own_authority("localhost", local));
+        assert!(!is_own_authority("[::1]", local));
+        assert!(is_own_authority("localhost", "127.0.0.1:80".parse().unwrap()));
+    }
+
+    #[test]
+    fn test_screen_png_scales_the_frame() {
+        let png = screen_png(&Tamagochi::new(), 1).unwrap();
+        let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+        let mut data = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut data).unwrap();
+        assert_eq!((reader.info().width, reader.info().height), (128, 64));
+        // Something is drawn, but far from everything
+        let lit: u32 = data.iter().map(|b| b.count_ones()).sum();
+        assert!(lit > 50 && lit < 128 * 64 / 2);
+    }
+}
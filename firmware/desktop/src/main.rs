@@ -3,60 +3,213 @@
 //! This is a command-line implementation for testing and development.
 //! The core logic is identical to what runs on ESP32.
 
+mod cli;
+mod clock;
+mod commands;
+mod config;
+mod daemon;
+mod events;
+mod hooks;
+#[cfg(feature = "http")]
+mod http;
+mod minigames;
+mod mqtt;
+mod oneshot;
+mod prompt;
+mod signing;
+mod slots;
 mod sprites;
 use tamagochi_core as core;
 
+use std::env;
 use std::io::{self, Write};
+use std::process;
 use std::thread;
 use std::time::Duration;
-use core::{Tamagochi, GameEvent};
+use cli::{Args, Command};
+use config::Config;
+use core::{Action, GameEvent, GameSession, Listener, Tamagochi};
+use events::{EventLog, Recorder};
+use hooks::Hooks;
+use slots::{Slot, SlotError, SlotStore};
 
 fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args = match cli::parse(&args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    match run(args) {
+        Ok(code) => process::exit(code),
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
+}
+
+/// Run a command, returning the exit status
+fn run(args: Args) -> Result<i32, SlotError> {
+    let store = SlotStore::open()?.allow_tampered(args.allow_tampered);
+    let config = Config::load(store.dir())?;
+    let mut log = EventLog::open(args.events_out.as_deref())?.with_hooks(Hooks::new(config.hooks.clone(), store.dir()));
+    match args.command {
+        Command::Interactive => {
+            let name = pick_slot(&store)?;
+            let _lock = store.lock(&name)?;
+            let slot = store.load(&name)?;
+            store.set_active(&name)?;
+            play_session(&store, &config, &mut log, slot)?;
+        }
+        Command::List => commands::list(&store)?,
+        Command::New(name) => commands::new(&store, &name)?,
+        Command::Switch(name) => commands::switch(&store, &name)?,
+        Command::Delete(name) => commands::delete(&store, &name)?,
+        Command::Rename(old, new) => commands::rename(&store, &old, &new)?,
+        Command::ExportCode(name, qr) => commands::export_code(&store, name.as_deref(), qr)?,
+        Command::ImportCode(code, name) => {
+            commands::import_code(&store, &code, name.as_deref().unwrap_or("imported"))?
+        }
+        Command::Schema(kind) => commands::schema(kind)?,
+        Command::Act(action, name) => return oneshot::act(&store, &config, &mut log, action, name.as_deref()),
+        Command::Status(name, format) => return oneshot::status(&store, &config, &mut log, name.as_deref(), format),
+        Command::Prompt(name, format) => prompt::run(&store, &config, name.as_deref(), format.as_deref())?,
+        Command::PromptInit(shell) => prompt::init(&shell),
+        Command::Daemon(name, frontends) => daemon::run(store, &config, log, name.as_deref(), frontends)?,
+        Command::Client(request) => return daemon::client(store.dir(), request),
+        Command::Help => println!("{}", cli::USAGE),
+    }
+    Ok(0)
+}
+
+/// Ask which slot to play, defaulting to the active one
+///
+/// Returns the name of the slot, which exists.
+fn pick_slot(store: &SlotStore) -> Result<String, SlotError> {
+    let names = store.names()?;
+    if names.is_empty() {
+        print!("No save slots yet. Name your new Tamagochi [pet]: ");
+        return create_slot(store, "pet");
+    }
+
+    let active = store.active();
+    println!("Save slots:");
+    for (i, name) in names.iter().enumerate() {
+        let marker = if active.as_deref() == Some(name.as_str()) { " (active)" } else { "" };
+        println!("  {}) {}{}", i + 1, name, marker);
+    }
+    println!("  n) new slot");
+    print!("Pick a slot [{}]: ", active.as_deref().unwrap_or(&names[0]));
+    io::stdout().flush().unwrap();
+
+    let choice = read_line().unwrap_or_default();
+    let name = if choice.is_empty() {
+        active.unwrap_or_else(|| names[0].clone())
+    } else if choice == "n" {
+        let default = store.free_name("pet");
+        print!("Name for the new slot [{}]: ", default);
+        return create_slot(store, &default);
+    } else if let Some(name) = choice.parse::<usize>().ok().and_then(|i| names.get(i.wrapping_sub(1))) {
+        name.clone()
+    } else {
+        choice
+    };
+    if !store.exists(&name) {
+        return Err(SlotError::NotFound(name));
+    }
+    Ok(name)
+}
+
+/// Read the name of a new slot after a prompt and create it
+///
+/// An empty name means `default`; a name that's taken or invalid is
+/// asked for again while stdin is open.
+fn create_slot(store: &SlotStore, default: &str) -> Result<String, SlotError> {
+    loop {
+        io::stdout().flush().unwrap();
+        let input = read_line();
+        let name = match input.as_deref() {
+            None | Some("") => default.to_string(),
+            Some(name) => name.to_string(),
+        };
+        match store.create(&name) {
+            Ok(_) => return Ok(name),
+            Err(e @ (SlotError::Exists(_) | SlotError::InvalidName(_))) if input.is_some() => {
+                print!("{}. Another name [{}]: ", e, default);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Read a trimmed line from stdin, `None` once stdin is closed
+fn read_line() -> Option<String> {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
+/// Play with the pet in a slot until the player quits or the pet dies
+fn play_session(store: &SlotStore, config: &Config, log: &mut EventLog, mut slot: Slot) -> Result<(), SlotError> {
     println!("🐣 Tamagochi Classic v1.0.0 - Desktop Edition");
     println!("==============================================\n");
 
-    let mut tama = Tamagochi::new();
-    let mut running = true;
-    let mut frame: u8 = 0;
-
-    println!("Your Tamagochi is born! 🥚");
-    println!("Commands: (f)eed, (p)lay, (m)edicine, (c)lean, (s)tatus, (q)uit\n");
+    if !slot.pet.alive {
+        println!("Your Tamagochi in '{}' is no longer with us.", slot.name);
+        println!("A new egg of generation {} appears! 🥚", slot.pet.generation + 1);
+        slot.pet = slot.pet.next_generation();
+    } else if slot.pet.stage == core::LifeStage::Egg && slot.pet.age == 0 {
+        println!("Your Tamagochi is born! 🥚");
+    } else {
+        println!("Welcome back to '{}'!", slot.name);
+        catch_up_offline(&mut slot, config, log, &mut io::stdout())?;
+    }
+    if slot.meta.modified {
+        println!("⚠️  This save was edited outside the game and is marked as modified.");
+    }
+    print_commands(&slot.pet);
     
     // Show initial sprite
-    display_tamagochi(&tama, frame);
+    display_tamagochi(&slot.pet, 0);
+
+    let recorder = Recorder::new(log, &slot.name);
+    let mut session = GameSession::new(slot.pet, (Console { frame: 0 }, recorder));
+    let mut running = true;
 
-    while running && tama.alive {
+    while running && session.tama().alive {
         print!("> ");
         io::stdout().flush().unwrap();
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input).unwrap();
-        let input = input.trim();
+        let Some(input) = read_line() else {
+            break;
+        };
 
-        match input {
+        let frame = session.listener().0.frame;
+        match input.as_str() {
             "f" | "feed" => {
-                tama.feed();
-                println!("🍔 You fed your Tamagochi!");
-                display_tamagochi(&tama, frame);
-            }
-            "p" | "play" => {
-                tama.play();
-                println!("🎮 You played with your Tamagochi!");
-                display_tamagochi(&tama, frame);
+                session.perform(Action::Feed);
             }
+            "p" | "play" => play_minigame(&mut session),
             "m" | "medicine" => {
-                tama.medicine();
-                println!("💊 You gave medicine to your Tamagochi!");
-                display_tamagochi(&tama, frame);
+                session.perform(Action::Medicine);
             }
             "c" | "clean" => {
-                tama.clean();
-                println!("🧹 You cleaned your Tamagochi!");
-                display_tamagochi(&tama, frame);
+                session.perform(Action::Clean);
+            }
+            "w" | "warm" => {
+                session.perform(Action::Warm);
             }
             "s" | "status" => {
-                display_status(&tama);
-                display_tamagochi(&tama, frame);
+                let tama = session.tama();
+                display_status(tama);
+                display_tamagochi(tama, frame);
+                print_commands(tama);
             }
             "q" | "quit" => {
                 running = false;
@@ -64,38 +217,160 @@ fn main() {
             }
             "tick" => {
                 // Manual tick for testing
-                if let Some(event) = tama.update() {
-                    handle_event(event, &tama, frame);
-                }
-            }
-            _ => {
-                println!("❓ Unknown command. Try: f, p, m, c, s, q");
+                session.update();
             }
+            _ => println!("❓ Unknown command. Try: f, p, m, c, w, s, q"),
         }
+        session.listener_mut().1.take_result()?;
 
         // Simulate time passing (1 second = 1 tick)
         thread::sleep(Duration::from_millis(100));
-        frame = frame.wrapping_add(1);
-        if let Some(event) = tama.update() {
-            handle_event(event, &tama, frame);
-        }
+        session.listener_mut().0.frame = frame.wrapping_add(1);
+        session.update();
+        session.listener_mut().1.take_result()?;
+
+        slot.pet = *session.tama();
+        slot.meta.last_played = slots::now();
+        store.save(&mut slot)?;
     }
 
-    if !tama.alive {
+    let (tama, (Console { frame }, _)) = session.into_parts();
+    slot.pet = tama;
+    if tama.passed_away() {
+        println!("\n🕊️  Your Tamagochi lived a long life and passed away peacefully.");
+        println!("   Age: {} days, care mistakes: {}", tama.age / 86400, tama.care_mistakes);
+        display_tamagochi(&tama, frame);
+    } else if !tama.alive {
         println!("\n💀 Your Tamagochi has died. RIP.");
         println!("   Age: {} days", tama.age / 86400);
         display_tamagochi(&tama, frame);
     }
+
+    slot.meta.last_played = slots::now();
+    store.save(&mut slot)
+}
+
+/// Let the pet live through the time since its last save
+///
+/// Clock anomalies are reported to `out` rather than applied silently.
+fn catch_up_offline(slot: &mut Slot, config: &Config, log: &mut EventLog, out: &mut dyn Write) -> io::Result<()> {
+    let now = clock::Reading::now();
+    let catch_up = clock::catch_up(&slot.meta.clock, now, config.max_catch_up_secs());
+    for anomaly in &catch_up.anomalies {
+        writeln!(out, "⏰ {}", anomaly)?;
+    }
+    if catch_up.ticks == 0 {
+        return Ok(());
+    }
+
+    writeln!(out, "While you were away ({}):", clock::format_duration(catch_up.ticks))?;
+    // Every tick is a second, ending now
+    let start = now.wall.saturating_sub(catch_up.ticks);
+    let recorder = Recorder::new(log, &slot.name).replaying(start, slot.pet.age);
+    let mut session = GameSession::new(slot.pet, (Events::default(), recorder));
+    session.advance(u32::try_from(catch_up.ticks).unwrap_or(u32::MAX));
+    let (pet, (events, mut recorder)) = session.into_parts();
+    slot.pet = pet;
+    for event in events.0 {
+        let message = match event {
+            GameEvent::Hatched => "🐣 Your egg hatched!".to_string(),
+            GameEvent::Evolution(stage) => format!("✨ Your Tamagochi evolved to: {:?}", stage),
+            GameEvent::Death => "💀 Your Tamagochi died while you were gone.".to_string(),
+            GameEvent::PassedAway => "🕊️  Your Tamagochi passed away of old age.".to_string(),
+            GameEvent::Birthday(days) => format!("🎂 Your Tamagochi turned {} days old!", days),
+            GameEvent::NeedsAttention => continue,
+        };
+        writeln!(out, "  {}", message)?;
+    }
+    writeln!(out)?;
+    recorder.take_result()
+}
+
+/// Play a mini-game and apply its outcome
+fn play_minigame(session: &mut Session) {
+    let tama = *session.tama();
+    if !tama.can_perform(Action::Play) {
+        // Refused, which the listeners report like any other action
+        session.perform(Action::Play);
+        return;
+    }
+    match minigames::play(&tama, session.listener().0.frame) {
+        Some(result) => {
+            session.apply_minigame(result);
+        }
+        // Backing out is only logged, there's nothing to show
+        None => session.listener_mut().1.on_action(Action::Play, false, &tama),
+    }
+}
+
+fn action_message(action: Action) -> &'static str {
+    match action {
+        Action::Feed => "🍔 You fed your Tamagochi!",
+        Action::Play => "🎮 You played with your Tamagochi!",
+        Action::Medicine => "💊 You gave medicine to your Tamagochi!",
+        Action::Clean => "🧹 You cleaned your Tamagochi!",
+        Action::Warm => "🔥 You kept the egg warm!",
+    }
+}
+
+fn action_verb(action: Action) -> &'static str {
+    match action {
+        Action::Feed => "feed",
+        Action::Play => "play with",
+        Action::Medicine => "give medicine to",
+        Action::Clean => "clean",
+        Action::Warm => "warm",
+    }
+}
+
+/// Print the command list, greying out actions not available in this stage
+fn print_commands(tama: &Tamagochi) {
+    let labels = [
+        (Action::Feed, "(f)eed"),
+        (Action::Play, "(p)lay"),
+        (Action::Medicine, "(m)edicine"),
+        (Action::Clean, "(c)lean"),
+        (Action::Warm, "(w)arm"),
+    ];
+    let actions: Vec<String> = labels
+        .iter()
+        .map(|&(action, label)| {
+            if tama.can_perform(action) {
+                label.to_string()
+            } else {
+                format!("\x1b[2m{}\x1b[0m", label)
+            }
+        })
+        .collect();
+    println!("Commands: {}, (s)tatus, (q)uit\n", actions.join(", "));
 }
 
 fn display_tamagochi(tama: &Tamagochi, frame: u8) {
+    if tama.passed_away() {
+        println!("{}", sprites::get_passed_away_sprite());
+        return;
+    }
     if !tama.alive {
         println!("{}", sprites::get_death_sprite());
         return;
     }
 
-    let sprite = sprites::get_sprite(tama.stage, frame);
+    let sprite = if tama.is_wiggling() {
+        sprites::get_wiggle_sprite(frame).to_string()
+    } else {
+        sprites::get_mood_sprite(tama.stage, tama.mood, frame)
+    };
     println!("{}", sprite);
+    if tama.stage != core::LifeStage::Egg {
+        println!("Mood: {:?}", tama.mood);
+    }
+
+    if let Some(ticks) = tama.ticks_until_hatch() {
+        println!("🌡️  Warmth: {} {}  (hatching in ~{} ticks)", tama.warmth, bar(tama.warmth), ticks);
+        if tama.is_wiggling() {
+            println!("🥚 The egg is wiggling! Warm it now!");
+        }
+    }
 
     // Show quick status indicators
     let needs = tama.needs_status();
@@ -124,11 +399,14 @@ fn display_status(tama: &Tamagochi) {
     println!("│   TAMAGOCHI STATUS          │");
     println!("├─────────────────────────────┤");
     println!("│ Stage:     {:?}", summary.stage);
-    println!("│ Age:       {} days", summary.age_days);
+    println!("│ Age:       {} days (generation {})", summary.age_days, tama.generation);
     println!("│ Hunger:    {} {}", summary.hunger_level, bar(summary.hunger_level));
     println!("│ Happiness: {} {}", summary.happiness_level, bar(summary.happiness_level));
     println!("│ Health:    {} {}", summary.health_level, bar(summary.health_level));
     println!("│ Care:      {} {}", summary.care_rating, bar(summary.care_rating));
+    println!("│ Weight:    {}g", summary.weight);
+    println!("│ Mood:      {:?}", summary.mood);
+    println!("│ Mistakes:  {}", tama.care_mistakes);
     println!("└─────────────────────────────┘\n");
 
     let needs = tama.needs_status();
@@ -143,26 +421,70 @@ fn display_status(tama: &Tamagochi) {
     }
 }
 
-fn handle_event(event: GameEvent, tama: &Tamagochi, frame: u8) {
-    match event {
-        GameEvent::Evolution(stage) => {
-            println!("\n✨ EVOLUTION! Your Tamagochi evolved to: {:?}", stage);
-            display_tamagochi(tama, frame);
-        }
-        GameEvent::Death => {
-            println!("\n💀 OH NO! Your Tamagochi died!");
-            display_tamagochi(tama, frame);
-        }
-        GameEvent::NeedsAttention => {
-            println!("\n🔔 Your Tamagochi needs attention!");
+/// A live session: the pet, printed to the terminal and logged
+type Session<'a> = GameSession<(Console, Recorder<'a>)>;
+
+/// Shows what happens to the pet in the terminal
+struct Console {
+    /// Animation frame of the sprites
+    frame: u8,
+}
+
+impl Listener for Console {
+    fn on_event(&mut self, event: GameEvent, tama: &Tamagochi) {
+        match event {
+            GameEvent::Hatched => {
+                for sprite in sprites::get_hatching_frames() {
+                    println!("{}", sprite);
+                    thread::sleep(Duration::from_millis(400));
+                }
+                println!("\n🐣 HATCHED! Your Tamagochi is a baby now!");
+                display_tamagochi(tama, self.frame);
+                print_commands(tama);
+            }
+            GameEvent::Evolution(stage) => {
+                println!("\n✨ EVOLUTION! Your Tamagochi evolved to: {:?}", stage);
+                display_tamagochi(tama, self.frame);
+                print_commands(tama);
+            }
+            GameEvent::Death => {
+                println!("\n💀 OH NO! Your Tamagochi died!");
+                display_tamagochi(tama, self.frame);
+            }
+            GameEvent::PassedAway => {
+                println!("\n🕊️  Your Tamagochi has passed away of old age.");
+                display_tamagochi(tama, self.frame);
+            }
+            GameEvent::NeedsAttention => {
+                println!("\n🔔 Your Tamagochi needs attention!");
+            }
+            GameEvent::Birthday(days) => {
+                println!("\n🎂 Birthday! Your Tamagochi is {} days old!", days);
+                display_tamagochi(tama, self.frame);
+            }
         }
-        GameEvent::Birthday(days) => {
-            println!("\n🎂 Birthday! Your Tamagochi is {} days old!", days);
-            display_tamagochi(tama, frame);
+    }
+
+    fn on_action(&mut self, action: Action, performed: bool, tama: &Tamagochi) {
+        if performed {
+            println!("{}", action_message(action));
+            display_tamagochi(tama, self.frame);
+        } else {
+            println!("🚫 You can't {} your Tamagochi right now ({:?}).", action_verb(action), tama.stage);
         }
     }
 }
 
+/// Collects the events of a session, to report them afterwards
+#[derive(Default)]
+struct Events(Vec<GameEvent>);
+
+impl Listener for Events {
+    fn on_event(&mut self, event: GameEvent, _tama: &Tamagochi) {
+        self.0.push(event);
+    }
+}
+
 fn bar(value: u8) -> String {
     let filled = (value as usize) / 10;
     let empty = 10 - filled;
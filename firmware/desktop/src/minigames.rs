@@ -0,0 +1,99 @@
+//! Terminal front-end for the core mini-games
+
+use crate::core::minigame::{Direction, Input, MiniGame, MiniGameKind, Phase, ROUNDS};
+use crate::core::{MiniGameResult, Tamagochi};
+use crate::sprites;
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Let the player pick a mini-game and play it to the end
+///
+/// Returns `None` if the player backs out of the menu or closes stdin.
+pub fn play(tama: &Tamagochi, frame: u8) -> Option<MiniGameResult> {
+    println!("🎮 Pick a game: (1) Which way? (2) Reaction  (anything else to cancel)");
+    let kind = match read_line()?.as_str() {
+        "1" => MiniGameKind::Guess,
+        "2" => MiniGameKind::Reaction,
+        _ => return None,
+    };
+
+    let clock = Instant::now();
+    let now = || clock.elapsed().as_millis() as u32;
+    let mut game = MiniGame::new(kind, seed(), now());
+
+    loop {
+        game.update(now());
+        match game.phase() {
+            Phase::Choose => {
+                println!("{}", sprites::get_sprite(tama.stage, frame));
+                print!("Round {}/{}: which way will it turn? (l/r) ", game.round(), ROUNDS);
+                io::stdout().flush().unwrap();
+                let input = match read_line()?.as_str() {
+                    "l" | "left" => Input::Left,
+                    "r" | "right" => Input::Right,
+                    _ => continue,
+                };
+                game.input(input, now());
+            }
+            Phase::Reveal { turned, won } => {
+                let arrow = match turned {
+                    Direction::Left => "⬅️  It turned left!",
+                    Direction::Right => "➡️  It turned right!",
+                };
+                println!("{} {}", arrow, if won { "✅ You got it!" } else { "❌ Missed." });
+                wait_for_next_phase(&mut game, now);
+            }
+            Phase::Wait => {
+                println!("Round {}/{}: wait for it... (press Enter on GO!)", game.round(), ROUNDS);
+                while game.phase() == Phase::Wait {
+                    thread::sleep(Duration::from_millis(10));
+                    game.update(now());
+                }
+            }
+            Phase::Go => {
+                print!("❗ GO! ");
+                io::stdout().flush().unwrap();
+                // Lines typed before the signal arrive instantly and count
+                // as a false start in the core rules
+                read_line()?;
+                game.input(Input::Press, now());
+            }
+            Phase::RoundOver { won } => {
+                println!("{}", if won { "⚡ Quick as lightning!" } else { "🐢 Too early or too slow." });
+                wait_for_next_phase(&mut game, now);
+            }
+            Phase::Finished(result) => {
+                println!("🏁 You won {} of {} rounds!", result.wins, result.rounds);
+                return Some(result);
+            }
+        }
+    }
+}
+
+/// Sleep until the result display is over
+fn wait_for_next_phase(game: &mut MiniGame, now: impl Fn() -> u32) {
+    let phase = game.phase();
+    while game.phase() == phase {
+        thread::sleep(Duration::from_millis(50));
+        game.update(now());
+    }
+}
+
+/// Read a trimmed line, or `None` once stdin is closed
+fn read_line() -> Option<String> {
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(input.trim().to_string()),
+    }
+}
+
+/// Seed from the wall clock so every game is different
+fn seed() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(1)
+}
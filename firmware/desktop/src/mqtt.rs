@@ -0,0 +1,603 @@
+//! MQTT bridge for Home Assistant
+//!
+//! `tamagochi-cli mqtt --broker <host:port> [name]` runs the daemon and
+//! mirrors its pet to an MQTT 3.1.1 broker. With the default `[mqtt]`
+//! settings and a slot called `pet`:
+//!
+//! | Topic                              | Payload                                |
+//! |------------------------------------|----------------------------------------|
+//! | `tamagochi/pet/hunger`             | `0`..`100` (100 = starving), retained  |
+//! | `tamagochi/pet/happiness`          | `0`..`100`, retained                   |
+//! | `tamagochi/pet/health`             | `0`..`100`, retained                   |
+//! | `tamagochi/pet/stage`              | e.g. `Baby`, `Premium adult`, retained |
+//! | `tamagochi/pet/alive`              | `ON` or `OFF`, retained                |
+//! | `tamagochi/pet/availability`       | `online` or `offline` (last will)      |
+//! | `tamagochi/pet/command/<action>`   | Subscribed: any payload performs the action (`feed`, `play`, `medicine`, `clean`) |
+//!
+//! On connecting, the bridge also publishes retained Home Assistant
+//! discovery payloads under `homeassistant/`, so the pet shows up as a
+//! device with five sensors and four buttons. A lost connection is retried
+//! with a growing delay of up to 30 seconds; everything is published
+//! again on reconnecting.
+
+use serde_json::json;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::MqttConfig;
+use crate::core::{Action, Tamagochi};
+use crate::daemon::Daemon;
+use crate::prompt;
+
+/// How long one poll for incoming packets blocks
+const POLL: Duration = Duration::from_millis(250);
+
+/// Limit for connecting and for writes
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// First and longest wait before reconnecting
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Largest packet accepted from the broker
+const MAX_PACKET: usize = 64 * 1024;
+
+/// Actions with a command topic and a button
+const ACTIONS: [(&str, Action); 4] = [
+    ("feed", Action::Feed),
+    ("play", Action::Play),
+    ("medicine", Action::Medicine),
+    ("clean", Action::Clean),
+];
+
+/// Bridge the daemon's pet to `broker` until the daemon shuts down
+pub fn bridge(daemon: &Daemon, broker: &str, config: &MqttConfig) {
+    let topics = Topics::new(config, &daemon.slot_name());
+    let mut backoff = MIN_BACKOFF;
+    while daemon.is_running() {
+        let mut connected = false;
+        match session(daemon, broker, config, &topics, &mut connected) {
+            Ok(()) => return,
+            Err(e) => {
+                if connected {
+                    backoff = MIN_BACKOFF;
+                }
+                eprintln!(
+                    "warning: MQTT broker {}: {}; retrying in {}s",
+                    broker,
+                    e,
+                    backoff.as_secs()
+                );
+            }
+        }
+        let retry = Instant::now() + backoff;
+        while daemon.is_running() && Instant::now() < retry {
+            thread::sleep(POLL);
+        }
+        backoff = (backoff * 2).min(MAX_BACKOFF);
+    }
+}
+
+/// One connection, until the daemon shuts down or the connection fails
+fn session(
+    daemon: &Daemon,
+    broker: &str,
+    config: &MqttConfig,
+    topics: &Topics,
+    connected: &mut bool,
+) -> io::Result<()> {
+    let addr = broker
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no address"))?;
+    let mut conn = Connection::open(TcpStream::connect_timeout(&addr, TIMEOUT)?)?;
+
+    let client_id = format!("tamagochi-{}", topics.slot);
+    conn.send(&connect_packet(&client_id, &topics.availability(), config))?;
+    let deadline = Instant::now() + TIMEOUT;
+    loop {
+        match conn.poll()? {
+            Some(Packet::ConnAck(0)) => break,
+            Some(Packet::ConnAck(code)) => {
+                let message = format!("connection refused (code {})", code);
+                return Err(io::Error::new(io::ErrorKind::ConnectionRefused, message));
+            }
+            Some(_) => {}
+            None if Instant::now() > deadline => {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "no answer to CONNECT"));
+            }
+            None => {}
+        }
+    }
+    *connected = true;
+    println!("📡 Connected to MQTT broker {}", broker);
+
+    for (topic, payload) in topics.discovery() {
+        conn.send(&publish_packet(&topic, payload.as_bytes(), true))?;
+    }
+    conn.send(&publish_packet(&topics.availability(), b"online", true))?;
+    conn.send(&subscribe_packet(1, &topics.command("+")))?;
+
+    let keep_alive = Duration::from_secs(config.keep_alive_secs().into());
+    let mut published: Option<[String; 5]> = None;
+    let mut last_sent = Instant::now();
+    let mut last_heard = Instant::now();
+    let mut last_ping = Instant::now();
+    while daemon.is_running() {
+        while let Some(packet) = conn.poll()? {
+            last_heard = Instant::now();
+            if let Packet::Publish { topic, .. } = packet {
+                if let Some(action) = topics.action(&topic) {
+                    daemon.perform(action);
+                }
+            }
+        }
+
+        // Only what changed, so every tick isn't a burst of messages
+        let state = state_payloads(&daemon.pet());
+        for (i, (key, _)) in STATE_KEYS.iter().enumerate() {
+            if published.as_ref().is_none_or(|old| old[i] != state[i]) {
+                conn.send(&publish_packet(&topics.state(key), state[i].as_bytes(), true))?;
+                last_sent = Instant::now();
+            }
+        }
+        published = Some(state);
+
+        // Ping when we've been quiet, so the broker keeps us, and when it
+        // has, so it answers if it's still there; each on its own clock, as
+        // a busy bridge may hear nothing back for a long time
+        let quiet = last_sent.elapsed() >= keep_alive / 2 || last_heard.elapsed() >= keep_alive / 2;
+        if quiet && last_ping.elapsed() >= keep_alive / 2 {
+            conn.send(&[0xc0, 0])?; // PINGREQ
+            last_sent = Instant::now();
+            last_ping = Instant::now();
+        }
+        if last_heard.elapsed() > keep_alive {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "broker stopped answering"));
+        }
+    }
+
+    // A clean disconnect skips the last will, so say goodbye ourselves
+    conn.send(&publish_packet(&topics.availability(), b"offline", true))?;
+    conn.send(&[0xe0, 0]) // DISCONNECT
+}
+
+/// Names of the state topics and their Home Assistant sensors
+const STATE_KEYS: [(&str, &str); 5] = [
+    ("hunger", "Hunger"),
+    ("happiness", "Happiness"),
+    ("health", "Health"),
+    ("stage", "Stage"),
+    ("alive", "Alive"),
+];
+
+/// Payloads of the state topics, in the order of [`STATE_KEYS`]
+fn state_payloads(tama: &Tamagochi) -> [String; 5] {
+    let summary = tama.status_summary();
+    [
+        summary.hunger_level.to_string(),
+        summary.happiness_level.to_string(),
+        summary.health_level.to_string(),
+        prompt::stage_name(summary.stage),
+        if tama.alive { "ON" } else { "OFF" }.to_string(),
+    ]
+}
+
+/// Topic names for one slot
+struct Topics {
+    slot: String,
+    /// `<base_topic>/<slot>`
+    base: String,
+    discovery_prefix: String,
+}
+
+impl Topics {
+    fn new(config: &MqttConfig, slot: &str) -> Self {
+        Self {
+            slot: slot.to_string(),
+            base: format!("{}/{}", config.base_topic, slot),
+            discovery_prefix: config.discovery_prefix.clone(),
+        }
+    }
+
+    fn state(&self, key: &str) -> String {
+        format!("{}/{}", self.base, key)
+    }
+
+    fn availability(&self) -> String {
+        format!("{}/availability", self.base)
+    }
+
+    fn command(&self, action: &str) -> String {
+        format!("{}/command/{}", self.base, action)
+    }
+
+    /// The action of a command topic
+    fn action(&self, topic: &str) -> Option<Action> {
+        let name = topic.strip_prefix(&self.base)?.strip_prefix("/command/")?;
+        ACTIONS.iter().find(|(n, _)| *n == name).map(|&(_, action)| action)
+    }
+
+    /// Home Assistant discovery topics and payloads
+    fn discovery(&self) -> Vec<(String, String)> {
+        let id = format!("tamagochi_{}", self.slot);
+        let device = json!({
+            "identifiers": [id],
+            "name": format!("Tamagochi {}", self.slot),
+            "manufacturer": "Tamagochi Classic",
+            "model": "tamagochi-cli",
+        });
+        let mut configs = Vec::new();
+        for (key, name) in STATE_KEYS {
+            let mut config = json!({
+                "name": name,
+                "unique_id": format!("{}_{}", id, key),
+                "state_topic": self.state(key),
+                "availability_topic": self.availability(),
+                "device": device,
+            });
+            let component = match key {
+                "alive" => "binary_sensor",
+                "stage" => "sensor",
+                _ => {
+                    config["unit_of_measurement"] = json!("%");
+                    config["state_class"] = json!("measurement");
+                    "sensor"
+                }
+            };
+            configs.push((component, format!("{}_{}", id, key), config));
+        }
+        for (action, _) in ACTIONS {
+            let mut name = action.to_string();
+            name[..1].make_ascii_uppercase();
+            let config = json!({
+                "name": name,
+                "unique_id": format!("{}_{}", id, action),
+                "command_topic": self.command(action),
+                "availability_topic": self.availability(),
+                "device": device,
+            });
+            configs.push(("button", format!("{}_{}", id, action), config));
+        }
+        configs
+            .into_iter()
+            .map(|(component, object_id, config)| {
+                let topic = format!("{}/{}/{}/config", self.discovery_prefix, component, object_id);
+                (topic, config.to_string())
+            })
+            .collect()
+    }
+}
+
+/// A packet from the broker
+#[derive(Debug, PartialEq)]
+enum Packet {
+    /// CONNACK with its return code
+    ConnAck(u8),
+    Publish { topic: String, payload: Vec<u8> },
+    /// SUBACK, PINGRESP and anything else that needs no handling
+    Other(u8),
+}
+
+/// A broker connection with its partly received packets
+struct Connection {
+    stream: TcpStream,
+    buf: Vec<u8>,
+}
+
+impl Connection {
+    fn open(stream: TcpStream) -> io::Result<Self> {
+        stream.set_read_timeout(Some(POLL))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        Ok(Self { stream, buf: Vec::new() })
+    }
+
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.stream.write_all(packet)
+    }
+
+    /// The next packet, or `None` if none arrived within [`POLL`]
+    fn poll(&mut self) -> io::Result<Option<Packet>> {
+        if let Some(packet) = self.take_packet()? {
+            return Ok(Some(packet));
+        }
+        let mut chunk = [0u8; 4096];
+        match self.stream.read(&mut chunk) {
+            Ok(0) => Err(io::Error::new(io::ErrorKind::ConnectionAborted, "connection closed")),
+            Ok(n) => {
+                self.buf.extend_from_slice(&chunk[..n]);
+                self.take_packet()
+            }
+            Err(e) if matches!(e.kind(), io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove and decode the first packet in the buffer, if it is complete
+    fn take_packet(&mut self) -> io::Result<Option<Packet>> {
+        let Some((header, body)) = split_packet(&self.buf)? else {
+            return Ok(None);
+        };
+        let packet = decode_packet(self.buf[0], &self.buf[header..header + body])?;
+        self.buf.drain(..header + body);
+        Ok(Some(packet))
+    }
+}
+
+fn malformed(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("malformed packet: {}", what))
+}
+
+/// Length of the fixed header and of the rest of the packet at the start of
+/// `buf`, `None` if it isn't all there yet
+fn split_packet(buf: &[u8]) -> io::Result<Option<(usize, usize)>> {
+    let mut len = 0usize;
+    for i in 1..5 {
+        let Some(&byte) = buf.get(i) else {
+            return Ok(None);
+        };
+        len |= usize::from(byte & 0x7f) << (7 * (i - 1));
+        if byte & 0x80 == 0 {
+            if len > MAX_PACKET {
+                return Err(malformed("too long"));
+            }
+            return Ok((buf.len() >= i + 1 + len).then_some((i + 1, len)));
+        }
+    }
+    Err(malformed("bad remaining length"))
+}
+
+fn decode_packet(first: u8, body: &[u8]) -> io::Result<Packet> {
+    match first >> 4 {
+        2 => Ok(Packet::ConnAck(*body.get(1).ok_or_else(|| malformed("short CONNACK"))?)),
+        3 => {
+            let (topic, rest) = read_string(body).ok_or_else(|| malformed("PUBLISH topic"))?;
+            // QoS 1 and 2 carry a packet id before the payload
+            let payload = if first & 0x06 != 0 { rest.get(2..) } else { Some(rest) };
+            let payload = payload.ok_or_else(|| malformed("PUBLISH packet id"))?;
+            Ok(Packet::Publish {
+                topic,
+                payload: payload.to_vec(),
+            })
+        }
+        kind => Ok(Packet::Other(kind)),
+    }
+}
+
+fn read_string(data: &[u8]) -> Option<(String, &[u8])> {
+    let len = usize::from(u16::from_be_bytes([*data.first()?, *data.get(1)?]));
+    let text = data.get(2..2 + len)?;
+    Some((String::from_utf8(text.to_vec()).ok()?, &data[2 + len..]))
+}
+
+fn put_string(out: &mut Vec<u8>, data: &[u8]) {
+    out.extend_from_slice(&(data.len() as u16).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+/// Prepend the fixed header with its variable-length remaining length
+fn packet(first: u8, body: Vec<u8>) -> Vec<u8> {
+    let mut out = vec![first];
+    let mut len = body.len();
+    loop {
+        let byte = (len % 128) as u8;
+        len /= 128;
+        if len == 0 {
+            out.push(byte);
+            break;
+        }
+        out.push(byte | 0x80);
+    }
+    out.extend(body);
+    out
+}
+
+/// CONNECT with a clean session and a retained `offline` last will
+fn connect_packet(client_id: &str, will_topic: &str, config: &MqttConfig) -> Vec<u8> {
+    let mut flags = 0x02 | 0x04 | 0x20; // clean session, will, will retain
+    if config.username.is_some() {
+        flags |= 0x80;
+    }
+    if config.password.is_some() {
+        flags |= 0x40;
+    }
+    let mut body = Vec::new();
+    put_string(&mut body, b"MQTT");
+    body.push(4); // protocol level 3.1.1
+    body.push(flags);
+    body.extend_from_slice(&config.keep_alive_secs().to_be_bytes());
+    put_string(&mut body, client_id.as_bytes());
+    put_string(&mut body, will_topic.as_bytes());
+    put_string(&mut body, b"offline");
+    for field in [&config.username, &config.password].into_iter().flatten() {
+        put_string(&mut body, field.as_bytes());
+    }
+    packet(0x10, body)
+}
+
+/// PUBLISH at QoS 0
+fn publish_packet(topic: &str, payload: &[u8], retain: bool) -> Vec<u8> {
+    let mut body = Vec::new();
+    put_string(&mut body, topic.as_bytes());
+    body.extend_from_slice(payload);
+    packet(0x30 | u8::from(retain), body)
+}
+
+/// SUBSCRIBE to one filter at QoS 0
+fn subscribe_packet(id: u16, filter: &str) -> Vec<u8> {
+    let mut body = id.to_be_bytes().to_vec();
+    put_string(&mut body, filter.as_bytes());
+    body.push(0);
+    packet(0x82, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::LifeStage;
+    use crate::events::EventLog;
+    use crate::slots::SlotStore;
+    use std::collections::HashMap;
+    use std::net::TcpListener;
+
+    /// Read one packet the way a broker would: first byte and body
+    fn read_packet(stream: &mut TcpStream) -> io::Result<(u8, Vec<u8>)> {
+        let mut byte = [0u8];
+        stream.read_exact(&mut byte)?;
+        let first = byte[0];
+        let (mut len, mut shift) = (0usize, 0);
+        loop {
+            stream.read_exact(&mut byte)?;
+            len |= usize::from(byte[0] & 0x7f) << shift;
+            shift += 7;
+            if byte[0] & 0x80 == 0 {
+                break;
+            }
+        }
+        let mut body = vec![0; len];
+        stream.read_exact(&mut body)?;
+        Ok((first, body))
+    }
+
+    #[test]
+    fn test_codec_round_trip() {
+        let publish = publish_packet("a/b", &[7; 200], true);
+        assert_eq!(publish[0], 0x31);
+        assert_eq!(split_packet(&publish).unwrap(), Some((3, 205)));
+        assert_eq!(split_packet(&publish[..100]).unwrap(), None);
+        assert_eq!(
+            decode_packet(publish[0], &publish[3..]).unwrap(),
+            Packet::Publish {
+                topic: "a/b".to_string(),
+                payload: vec![7; 200]
+            }
+        );
+        assert!(split_packet(&[0x30, 0xff, 0xff, 0xff, 0xff, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_bridge_with_stand_in_broker() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-mqtt-{}", std::process::id()));
+        let store = SlotStore::at(dir.clone()).unwrap();
+        store.create("pet").unwrap();
+        let mut slot = store.load("pet").unwrap();
+        slot.pet.stage = LifeStage::Baby;
+        slot.pet.hunger = 80;
+        let daemon = Daemon::new(store, slot, EventLog::open(None).unwrap());
+
+        let broker = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = broker.local_addr().unwrap().to_string();
+        thread::spawn({
+            let daemon = std::sync::Arc::clone(&daemon);
+            move || bridge(&daemon, &addr, &MqttConfig::default())
+        });
+
+        // The first connection is dropped after CONNECT, so the bridge has to reconnect
+        let (mut stream, _) = broker.accept().unwrap();
+        assert_eq!(read_packet(&mut stream).unwrap().0, 0x10);
+        drop(stream);
+
+        let (mut stream, _) = broker.accept().unwrap();
+        let (first, connect) = read_packet(&mut stream).unwrap();
+        assert_eq!(first, 0x10);
+        assert_eq!(read_string(&connect[10..]).unwrap().0, "tamagochi-pet");
+        stream.write_all(&[0x20, 2, 0, 0]).unwrap();
+
+        let mut retained = HashMap::new();
+        let mut fed = false;
+        loop {
+            let (first, body) = read_packet(&mut stream).unwrap();
+            match first >> 4 {
+                3 => {
+                    let Packet::Publish { topic, payload } = decode_packet(first, &body).unwrap() else {
+                        unreachable!()
+                    };
+                    assert_eq!(first & 1, 1, "{} should be retained", topic);
+                    retained.insert(topic, String::from_utf8(payload).unwrap());
+                }
+                8 => {
+                    assert_eq!(read_string(&body[2..]).unwrap().0, "tamagochi/pet/command/+");
+                    stream.write_all(&[0x90, 3, body[0], body[1], 0]).unwrap();
+                    stream.write_all(&publish_packet("tamagochi/pet/command/feed", b"PRESS", false)).unwrap();
+                    fed = true;
+                }
+                _ => {}
+            }
+            // "alive" is the last state topic
+            let hunger = retained.get("tamagochi/pet/hunger");
+            if fed && retained.contains_key("tamagochi/pet/alive") && hunger.is_some_and(|h| h != "80") {
+                break;
+            }
+        }
+
+        assert_eq!(retained["tamagochi/pet/availability"], "online");
+        assert_eq!(retained["tamagochi/pet/stage"], "Baby");
+        assert_eq!(retained["tamagochi/pet/alive"], "ON");
+        let sensor: serde_json::Value =
+            serde_json::from_str(&retained["homeassistant/sensor/tamagochi_pet_health/config"]).unwrap();
+        assert_eq!(sensor["state_topic"], "tamagochi/pet/health");
+        let button: serde_json::Value =
+            serde_json::from_str(&retained["homeassistant/button/tamagochi_pet_feed/config"]).unwrap();
+        assert_eq!(button["command_topic"], "tamagochi/pet/command/feed");
+        assert_ne!(daemon.pet().hunger, 80);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_busy_bridge_pings_a_silent_broker() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-mqtt-ping-{}", std::process::id()));
+        let store = SlotStore::at(dir.clone()).unwrap();
+        let mut slot = store.create("pet").unwrap();
+        slot.pet.stage = LifeStage::Baby;
+        slot.pet.hunger = 90;
+        let daemon = Daemon::new(store, slot, EventLog::open(None).unwrap());
+        let config = MqttConfig {
+            keep_alive_secs: 2,
+            ..MqttConfig::default()
+        };
+
+        let broker = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = broker.local_addr().unwrap().to_string();
+        thread::spawn({
+            let daemon = std::sync::Arc::clone(&daemon);
+            move || bridge(&daemon, &addr, &config)
+        });
+        let (mut stream, _) = broker.accept().unwrap();
+        let (first, connect) = read_packet(&mut stream).unwrap();
+        assert_eq!(first, 0x10);
+        assert_eq!(connect[8..10], [0, 2]);
+        stream.write_all(&[0x20, 2, 0, 0]).unwrap();
+
+        // Keep the bridge publishing while the broker only answers pings,
+        // for well over the keep-alive
+        let start = Instant::now();
+        let busy = Duration::from_secs(5);
+        thread::spawn({
+            let daemon = std::sync::Arc::clone(&daemon);
+            move || {
+                for action in [Action::Feed, Action::Play].into_iter().cycle() {
+                    if start.elapsed() > busy {
+                        break;
+                    }
+                    daemon.perform(action);
+                    thread::sleep(Duration::from_millis(100));
+                }
+            }
+        });
+        let mut pings = 0;
+        while start.elapsed() < busy {
+            let (first, _) = read_packet(&mut stream).expect("the bridge stayed connected");
+            if first == 0xc0 {
+                pings += 1;
+                stream.write_all(&[0xd0, 0]).unwrap(); // PINGRESP
+            }
+        }
+        assert!(pings >= 2, "only {} pings", pings);
+
+        broker.set_nonblocking(true).unwrap();
+        assert!(broker.accept().is_err(), "the bridge reconnected");
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
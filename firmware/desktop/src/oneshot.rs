@@ -0,0 +1,180 @@
+//! One-shot commands for scripts and aliases
+//!
+//! `feed`, `play`, `medicine`, `clean`, `warm` and `status` lock a slot,
+//! catch its pet up on the time since the last save, do their thing, save
+//! and exit. The exit status tells how the pet is doing, so scripts can
+//! react without parsing output:
+//!
+//! ```sh
+//! tamagochi-cli status --short || notify-send "Your Tamagochi needs you"
+//! ```
+//!
+//! Catch-up reports and errors go to stderr, so `status --json` prints
+//! nothing but JSON on stdout.
+
+use std::io;
+
+use crate::cli::StatusFormat;
+use crate::config::Config;
+use crate::core::{Action, GameSession, LifeStage, Tamagochi};
+use crate::events::{EventLog, Recorder, StatusRecord};
+use crate::slots::{Slot, SlotError, SlotLock, SlotStore};
+
+/// Exit status: the pet is fine
+pub const EXIT_OK: i32 = 0;
+/// Exit status: the pet has a critical need
+pub const EXIT_NEEDS_ATTENTION: i32 = 3;
+/// Exit status: the pet is dead
+pub const EXIT_DEAD: i32 = 4;
+/// Exit status: the action isn't available in the pet's stage
+pub const EXIT_UNAVAILABLE: i32 = 5;
+
+/// `feed|play|medicine|clean|warm [name]`
+///
+/// `play` applies the plain play effect; the mini-games need the
+/// interactive mode.
+pub fn act(
+    store: &SlotStore,
+    config: &Config,
+    log: &mut EventLog,
+    action: Action,
+    name: Option<&str>,
+) -> Result<i32, SlotError> {
+    let (_lock, mut slot) = open(store, config, log, name)?;
+
+    let mut session = GameSession::new(slot.pet, Recorder::new(log, &slot.name));
+    let performed = session.perform(action);
+    let (pet, mut recorder) = session.into_parts();
+    slot.pet = pet;
+    recorder.take_result()?;
+    if performed {
+        println!("{}", crate::action_message(action));
+    } else if slot.pet.alive {
+        eprintln!(
+            "🚫 You can't {} your Tamagochi right now ({:?}).",
+            crate::action_verb(action),
+            slot.pet.stage
+        );
+    } else {
+        eprintln!("💀 Your Tamagochi in '{}' is no longer with us. Play to hatch a new egg.", slot.name);
+    }
+    store.save(&mut slot)?;
+
+    if performed || !slot.pet.alive {
+        Ok(exit_status(&slot.pet))
+    } else {
+        Ok(EXIT_UNAVAILABLE)
+    }
+}
+
+/// `status [name] [--json|--short]`
+pub fn status(
+    store: &SlotStore,
+    config: &Config,
+    log: &mut EventLog,
+    name: Option<&str>,
+    format: StatusFormat,
+) -> Result<i32, SlotError> {
+    let (_lock, mut slot) = open(store, config, log, name)?;
+    store.save(&mut slot)?;
+
+    let tama = &slot.pet;
+    match format {
+        StatusFormat::Text => crate::display_status(tama),
+        StatusFormat::Json => {
+            let status = serde_json::to_string(&StatusRecord::new(&slot)).map_err(io::Error::other)?;
+            println!("{}", status);
+        }
+        StatusFormat::Short => println!("{}: {}", slot.name, short_status(tama)),
+    }
+    Ok(exit_status(tama))
+}
+
+/// Lock and load a slot (the active one by default) and catch it up
+pub fn open(
+    store: &SlotStore,
+    config: &Config,
+    log: &mut EventLog,
+    name: Option<&str>,
+) -> Result<(SlotLock, Slot), SlotError> {
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => store.active().ok_or(SlotError::NoActive)?,
+    };
+    if !store.exists(&name) {
+        return Err(SlotError::NotFound(name));
+    }
+    let lock = store.lock(&name)?;
+    let mut slot = store.load(&name)?;
+    crate::catch_up_offline(&mut slot, config, log, &mut io::stderr())?;
+    slot.meta.last_played = crate::slots::now();
+    Ok((lock, slot))
+}
+
+/// The exit status for the pet's condition
+pub fn exit_status(tama: &Tamagochi) -> i32 {
+    let needs = tama.needs_status();
+    if !tama.alive {
+        EXIT_DEAD
+    } else if needs.hunger_critical || needs.happiness_critical || needs.health_critical {
+        EXIT_NEEDS_ATTENTION
+    } else {
+        EXIT_OK
+    }
+}
+
+/// One line like `Child, Happy — hunger 40, happiness 70, health 90 (HUNGRY)`
+fn short_status(tama: &Tamagochi) -> String {
+    if tama.passed_away() {
+        return "passed away".to_string();
+    }
+    if !tama.alive {
+        return "dead".to_string();
+    }
+    let summary = tama.status_summary();
+    let mut line = match summary.stage {
+        LifeStage::Egg => "Egg".to_string(),
+        LifeStage::Adult(form) => format!("{:?} adult, {:?}", form, summary.mood),
+        stage => format!("{:?}, {:?}", stage, summary.mood),
+    };
+    line += &format!(
+        " — hunger {}, happiness {}, health {}",
+        summary.hunger_level, summary.happiness_level, summary.health_level
+    );
+
+    let needs = tama.needs_status();
+    let warnings: Vec<&str> = [
+        (needs.hunger_critical, "HUNGRY"),
+        (needs.happiness_critical, "SAD"),
+        (needs.health_critical, "SICK"),
+    ]
+    .iter()
+    .filter(|(critical, _)| *critical)
+    .map(|&(_, warning)| warning)
+    .collect();
+    if !warnings.is_empty() {
+        line += &format!(" ({})", warnings.join(", "));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_status_reflects_the_pet() {
+        let mut tama = Tamagochi::new();
+        assert_eq!(exit_status(&tama), EXIT_OK);
+
+        tama.health = 5;
+        assert_eq!(exit_status(&tama), EXIT_NEEDS_ATTENTION);
+        assert!(short_status(&tama).ends_with("(SICK)"));
+
+        tama.alive = false;
+        assert_eq!(exit_status(&tama), EXIT_DEAD);
+        assert_eq!(short_status(&tama), "passed away");
+        tama.health = 0;
+        assert_eq!(short_status(&tama), "dead");
+    }
+}
@@ -0,0 +1,183 @@
+//! A tiny status indicator for shell prompts and tmux
+//!
+//! `tamagochi-cli prompt` prints one short line such as `🐣♥♥♡ 🍔!`. It
+//! only reads the slot: the pet is caught up in memory to show its
+//! current state, but nothing is saved or locked, so it stays fast enough
+//! to run on every prompt.
+//!
+//! The line comes from a format string (`[prompt] format` in config.toml
+//! or `--format`) with these placeholders:
+//!
+//! | Placeholder   | Example    | Content                                   |
+//! |---------------|------------|-------------------------------------------|
+//! | `{stage}`     | `🐣`       | Stage emoji (💀 dead, 🕊 passed away)      |
+//! | `{hearts}`    | `♥♥♡`      | Happiness as three hearts                 |
+//! | `{alerts}`    | `🍔! 🤒!`   | Critical needs, empty if there are none   |
+//! | `{name}`      | `pet`      | Slot name                                 |
+//! | `{stage_name}`| `Baby`     | Stage name                                |
+//! | `{mood}`      | `Happy`    | Mood                                      |
+//! | `{hunger}`    | `42`       | Hunger level (100 = starving)             |
+//! | `{happiness}` | `70`       | Happiness level                           |
+//! | `{health}`    | `95`       | Health level                              |
+//! | `{age}`       | `3`        | Age in days                               |
+//!
+//! `{{` and `}}` print literal braces; trailing spaces are trimmed.
+
+use crate::clock::{self, Reading};
+use crate::config::Config;
+use crate::core::{LifeStage, Tamagochi};
+use crate::slots::{SlotError, SlotStore};
+
+/// Format used when config.toml doesn't set one
+pub const DEFAULT_FORMAT: &str = "{stage}{hearts} {alerts}";
+
+/// Shells and tools `prompt --init` has a snippet for
+pub const SNIPPETS: [(&str, &str); 3] = [
+    (
+        "bash",
+        "# Add to ~/.bashrc\nPS1='$(tamagochi-cli prompt 2>/dev/null) '\"$PS1\"",
+    ),
+    (
+        "zsh",
+        "# Add to ~/.zshrc\nsetopt PROMPT_SUBST\nPROMPT='$(tamagochi-cli prompt 2>/dev/null) '\"$PROMPT\"",
+    ),
+    (
+        "tmux",
+        "# Add to ~/.tmux.conf\nset -g status-interval 15\nset -g status-right '#(tamagochi-cli prompt 2>/dev/null) %H:%M'",
+    ),
+];
+
+/// `prompt [name] [--format <format>]`: print the indicator line
+pub fn run(store: &SlotStore, config: &Config, name: Option<&str>, format: Option<&str>) -> Result<(), SlotError> {
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => store.active().ok_or(SlotError::NoActive)?,
+    };
+    let mut slot = store.load(&name)?;
+    if slot.pet.alive {
+        let catch_up = clock::catch_up(&slot.meta.clock, Reading::now(), config.max_catch_up_secs());
+        slot.pet
+            .advance(u32::try_from(catch_up.ticks).unwrap_or(u32::MAX), |_, _| {});
+    }
+
+    let format = format.unwrap_or(&config.prompt.format);
+    println!("{}", render(format, &slot.name, &slot.pet));
+    Ok(())
+}
+
+/// `prompt --init <shell>`: print the snippet for one of [`SNIPPETS`]
+pub fn init(shell: &str) {
+    if let Some((_, snippet)) = SNIPPETS.iter().find(|(name, _)| *name == shell) {
+        println!("{}", snippet);
+    }
+}
+
+/// Fill in the placeholders of `format`
+pub fn render(format: &str, name: &str, tama: &Tamagochi) -> String {
+    let mut out = String::new();
+    let mut rest = format;
+    while let Some(i) = rest.find(['{', '}']) {
+        out.push_str(&rest[..i]);
+        rest = &rest[i..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            out.push_str(&rest[..1]);
+            rest = &rest[2..];
+            continue;
+        }
+        let Some(end) = rest.find('}').filter(|_| rest.starts_with('{')) else {
+            // A lone brace prints as is
+            out.push_str(&rest[..1]);
+            rest = &rest[1..];
+            continue;
+        };
+        match placeholder(&rest[1..end], name, tama) {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out.trim_end().to_string()
+}
+
+/// The value of a placeholder, `None` for unknown ones
+fn placeholder(key: &str, name: &str, tama: &Tamagochi) -> Option<String> {
+    let summary = tama.status_summary();
+    Some(match key {
+        "stage" => stage_emoji(tama).to_string(),
+        "hearts" => {
+            let full = (summary.happiness_level as usize + 16) / 33;
+            format!("{}{}", "♥".repeat(full), "♡".repeat(3 - full))
+        }
+        "alerts" => {
+            let needs = tama.needs_status();
+            [
+                (needs.hunger_critical, "🍔!"),
+                (needs.happiness_critical, "😢!"),
+                (needs.health_critical, "🤒!"),
+            ]
+            .iter()
+            .filter(|(critical, _)| tama.alive && *critical)
+            .map(|&(_, alert)| alert)
+            .collect::<Vec<_>>()
+            .join(" ")
+        }
+        "name" => name.to_string(),
+        "stage_name" => stage_name(summary.stage),
+        "mood" => format!("{:?}", summary.mood),
+        "hunger" => summary.hunger_level.to_string(),
+        "happiness" => summary.happiness_level.to_string(),
+        "health" => summary.health_level.to_string(),
+        "age" => summary.age_days.to_string(),
+        _ => return None,
+    })
+}
+
+/// The stage as words, e.g. `Baby` or `Premium adult`
+pub fn stage_name(stage: LifeStage) -> String {
+    match stage {
+        LifeStage::Adult(form) => format!("{:?} adult", form),
+        stage => format!("{:?}", stage),
+    }
+}
+
+fn stage_emoji(tama: &Tamagochi) -> &'static str {
+    if tama.passed_away() {
+        return "🕊";
+    }
+    if !tama.alive {
+        return "💀";
+    }
+    match tama.stage {
+        LifeStage::Egg => "🥚",
+        LifeStage::Baby => "🐣",
+        LifeStage::Child => "🐥",
+        LifeStage::Teenager => "🐤",
+        LifeStage::Adult(_) => "🐔",
+        LifeStage::Elder => "🦉",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_placeholders() {
+        let mut tama = Tamagochi::new();
+        tama.stage = LifeStage::Baby;
+        tama.happiness = 70;
+        assert_eq!(render(DEFAULT_FORMAT, "pet", &tama), "🐣♥♥♡");
+        assert_eq!(
+            render("{name}: {hunger}/{health} {{x}} {nope} {", "pet", &tama),
+            "pet: 50/100 {x} {nope} {"
+        );
+
+        tama.health = 5;
+        assert_eq!(render(DEFAULT_FORMAT, "pet", &tama), "🐣♥♥♡ 🤒!");
+
+        tama.alive = false;
+        tama.health = 0;
+        assert_eq!(render(DEFAULT_FORMAT, "pet", &tama), "💀♥♥♡");
+    }
+}
@@ -0,0 +1,118 @@
+//! Per-install key for tamper-evident saves
+//!
+//! Every slot file carries an HMAC-SHA256 over its contents, keyed with a
+//! random secret created on first run in `<data dir>/key`. Editing a save
+//! by hand breaks the MAC, so the game can tell the pet was modified. This
+//! keeps the office leaderboard honest; it isn't meant to stop someone
+//! who reads the key file.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const KEY_LEN: usize = 32;
+
+/// Secret key for signing saves
+pub struct Key([u8; KEY_LEN]);
+
+impl Key {
+    /// Load the key from `path`, creating a random one if there is none
+    ///
+    /// Returns whether the key was just created.
+    pub fn load_or_create(path: &Path) -> io::Result<(Self, bool)> {
+        match fs::read_to_string(path) {
+            Ok(hex) => {
+                let bytes = from_hex(hex.trim())
+                    .filter(|bytes| bytes.len() == KEY_LEN)
+                    .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "key file is corrupt"))?;
+                let mut key = [0; KEY_LEN];
+                key.copy_from_slice(&bytes);
+                Ok((Self(key), false))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let mut key = [0; KEY_LEN];
+                getrandom::getrandom(&mut key).map_err(io::Error::other)?;
+                write_private(path, &to_hex(&key))?;
+                Ok((Self(key), true))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// MAC of `data` as lowercase hex
+    pub fn sign(&self, data: &[u8]) -> String {
+        to_hex(&self.mac(data).finalize().into_bytes())
+    }
+
+    /// Whether `mac` is the MAC of `data`, compared in constant time
+    pub fn verify(&self, data: &[u8], mac: &str) -> bool {
+        from_hex(mac).is_some_and(|mac| self.mac(data).verify_slice(&mac).is_ok())
+    }
+
+    fn mac(&self, data: &[u8]) -> HmacSha256 {
+        // HMAC accepts keys of any length
+        let mut mac = HmacSha256::new_from_slice(&self.0).expect("HMAC key");
+        mac.update(data);
+        mac
+    }
+}
+
+/// Write a file only the current user can read
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    io::Write::write_all(&mut options.open(path)?, contents.as_bytes())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edited_data_fails_verification() {
+        let key = Key([7; KEY_LEN]);
+        let mac = key.sign(b"hunger: 50");
+        assert!(key.verify(b"hunger: 50", &mac));
+        assert!(!key.verify(b"hunger: 10", &mac));
+        assert!(!key.verify(b"hunger: 50", &mac[2..]));
+        assert!(!key.verify(b"hunger: 50", "not hex"));
+        assert!(!Key([8; KEY_LEN]).verify(b"hunger: 50", &mac));
+    }
+
+    #[test]
+    fn test_key_is_created_once() {
+        let dir = std::env::temp_dir().join(format!("tamagochi-signing-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("key");
+
+        let (key, created) = Key::load_or_create(&path).unwrap();
+        assert!(created);
+        let (again, created) = Key::load_or_create(&path).unwrap();
+        assert!(!created);
+        assert!(again.verify(b"save", &key.sign(b"save")));
+
+        fs::write(&path, "beef").unwrap();
+        assert!(Key::load_or_create(&path).is_err());
+    }
+}
@@ -0,0 +1,687 @@
+//! Named save slots on disk
+//!
+//! Every slot is a JSON file in `<data dir>/slots/<name>.json` holding the
+//! versioned pet save plus some metadata. The name of the slot played
+//! last is kept in `<data dir>/active`.
+//!
+//! The data directory is `$TAMAGOCHI_HOME` if set, otherwise
+//! `$XDG_DATA_HOME/tamagochi` or `~/.local/share/tamagochi`.
+//!
+//! Slot files are signed with the install's [`Key`]. A slot whose MAC
+//! doesn't match was edited by hand; it is refused unless tampered saves
+//! are allowed, in which case the pet is marked as modified, for good once
+//! the slot is saved again. Pets that can't be verified at all, imported
+//! from a passcode or saved before signing, are marked as modified too.
+//!
+//! A process playing or changing a slot holds a lock on
+//! `<data dir>/locks/<name>.lock` (see [`SlotStore::lock`]).
+
+use crate::core::schema::{AnySave, SaveFile};
+use crate::clock::{ClockTrail, Reading};
+use crate::core::{PasscodeError, Tamagochi};
+use crate::signing::Key;
+
+use schemars::{JsonSchema, Schema};
+use schemars::generate::SchemaSettings;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File, TryLockError};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Longest allowed slot name
+const MAX_NAME_LEN: usize = 32;
+
+/// How long to wait for another process to finish with a slot
+const LOCK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Errors from slot management
+#[derive(Debug)]
+pub enum SlotError {
+    /// No slot with this name
+    NotFound(String),
+    /// No slot is active yet
+    NoActive,
+    /// A slot with this name already exists
+    Exists(String),
+    /// Names may only use letters, digits, `-` and `_`
+    InvalidName(String),
+    /// The slot file can't be parsed or migrated
+    Corrupt(String, String),
+    /// The slot file was edited outside the game
+    Tampered(String),
+    /// Another process is using the slot
+    Locked(String),
+    /// A passcode couldn't be decoded
+    Passcode(PasscodeError),
+    /// `config.toml` can't be parsed
+    Config(String),
+    /// Reading or writing failed
+    Io(io::Error),
+}
+
+impl fmt::Display for SlotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotError::NotFound(name) => write!(f, "no save slot named '{}'", name),
+            SlotError::NoActive => write!(f, "no active save slot, choose one with 'switch <name>'"),
+            SlotError::Exists(name) => write!(f, "save slot '{}' already exists", name),
+            SlotError::InvalidName(name) => write!(
+                f,
+                "invalid slot name '{}' (use up to {} letters, digits, '-' or '_')",
+                name, MAX_NAME_LEN
+            ),
+            SlotError::Corrupt(name, reason) => write!(f, "save slot '{}' is unreadable: {}", name, reason),
+            SlotError::Tampered(name) => write!(
+                f,
+                "save slot '{}' was edited outside the game (use --allow-tampered to load it anyway)",
+                name
+            ),
+            SlotError::Locked(name) => write!(
+                f,
+                "save slot '{}' is in use by another tamagochi-cli (if that's the daemon, use 'client')",
+                name
+            ),
+            SlotError::Passcode(e) => write!(f, "{}", e),
+            SlotError::Config(reason) => write!(f, "config.toml: {}", reason),
+            SlotError::Io(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for SlotError {
+    fn from(e: io::Error) -> Self {
+        SlotError::Io(e)
+    }
+}
+
+/// Metadata stored next to the pet
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SlotMeta {
+    /// When the slot was created (Unix seconds)
+    pub created: u64,
+    /// When the pet was last played with (Unix seconds)
+    pub last_played: u64,
+    /// The save was edited outside the game and loaded anyway
+    #[serde(default)]
+    pub modified: bool,
+    /// Clock readings at the last save, to spot clock changes
+    #[serde(default)]
+    pub clock: ClockTrail,
+}
+
+/// A loaded save slot
+#[derive(Clone, Debug)]
+pub struct Slot {
+    pub name: String,
+    pub meta: SlotMeta,
+    pub pet: Tamagochi,
+}
+
+/// Slot file as read from disk (any save version)
+#[derive(Deserialize)]
+struct SlotFileIn {
+    meta: SlotMeta,
+    save: AnySave,
+    /// Missing in saves from before signing
+    #[serde(default)]
+    mac: Option<String>,
+}
+
+/// The signed part of a slot file (current save version)
+#[derive(Serialize, JsonSchema)]
+struct SlotBody<'a> {
+    /// Slot metadata
+    meta: &'a SlotMeta,
+    /// The versioned pet save
+    save: SaveFile<'a>,
+}
+
+impl SlotBody<'_> {
+    /// The bytes covered by the MAC
+    fn signed_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+}
+
+/// Slot file as written to disk
+#[derive(Serialize, JsonSchema)]
+#[schemars(rename = "SlotFile")]
+struct SlotFileOut<'a> {
+    #[serde(flatten)]
+    body: SlotBody<'a>,
+    /// HMAC-SHA256 of the compact JSON of `meta` and `save` (hex)
+    mac: String,
+}
+
+/// JSON Schema of the slot files written by this version
+pub fn file_schema() -> Schema {
+    SchemaSettings::default()
+        .for_serialize()
+        .into_generator()
+        .into_root_schema_for::<SlotFileOut>()
+}
+
+/// Exclusive use of a slot by this process, released when dropped
+pub struct SlotLock {
+    _file: File,
+}
+
+/// The directory holding all slots
+pub struct SlotStore {
+    dir: PathBuf,
+    key: Key,
+    allow_tampered: bool,
+}
+
+impl SlotStore {
+    /// Open the slot store in the default data directory
+    pub fn open() -> Result<Self, SlotError> {
+        Self::at(data_dir())
+    }
+
+    /// Open a slot store in a specific directory
+    ///
+    /// On first run this creates the signing key and signs the saves
+    /// that were written before saves were signed.
+    pub fn at(dir: PathBuf) -> Result<Self, SlotError> {
+        fs::create_dir_all(dir.join("slots"))?;
+        fs::create_dir_all(dir.join("locks"))?;
+        let (key, created) = Key::load_or_create(&dir.join("key"))?;
+        let store = Self {
+            dir,
+            key,
+            allow_tampered: false,
+        };
+        if created {
+            store.adopt_unsigned()?;
+        }
+        Ok(store)
+    }
+
+    /// Load slots that were edited outside the game instead of refusing
+    /// them, marking their pets as modified
+    pub fn allow_tampered(mut self, allow: bool) -> Self {
+        self.allow_tampered = allow;
+        self
+    }
+
+    /// Sign slots written before saves were signed
+    ///
+    /// They can't be told from saves whose MAC was removed along with the
+    /// key, so their pets are marked as modified. Their clock trail stays
+    /// as it is, so the time since they were played still catches up.
+    fn adopt_unsigned(&self) -> Result<(), SlotError> {
+        for name in self.names()? {
+            let Ok(json) = fs::read_to_string(self.path(&name)) else {
+                continue;
+            };
+            let Ok(file) = serde_json::from_str::<SlotFileIn>(&json) else {
+                continue;
+            };
+            if file.mac.is_some() {
+                continue;
+            }
+            if let Ok(pet) = file.save.migrate() {
+                let slot = Slot {
+                    name,
+                    meta: SlotMeta {
+                        modified: true,
+                        ..file.meta
+                    },
+                    pet,
+                };
+                self.write(&slot)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The data directory
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join("slots").join(format!("{}.json", name))
+    }
+
+    /// Whether a slot exists
+    pub fn exists(&self, name: &str) -> bool {
+        self.path(name).exists()
+    }
+
+    /// Names of all slots, sorted
+    pub fn names(&self) -> Result<Vec<String>, SlotError> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(self.dir.join("slots"))? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
+                    names.push(stem.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// `base`, or `base-2`, `base-3`, ... if that slot exists already
+    pub fn free_name(&self, base: &str) -> String {
+        if !self.exists(base) {
+            return base.to_string();
+        }
+        (2..)
+            .map(|n| format!("{}-{}", base, n))
+            .find(|name| !self.exists(name))
+            .unwrap_or_default()
+    }
+
+    /// Create a slot with a fresh egg
+    pub fn create(&self, name: &str) -> Result<Slot, SlotError> {
+        self.create_with(name, Tamagochi::new(), false)
+    }
+
+    /// Create a slot holding a pet from outside the game, such as a
+    /// passcode
+    ///
+    /// Its history can't be verified, so the pet is marked as modified.
+    pub fn import(&self, name: &str, pet: Tamagochi) -> Result<Slot, SlotError> {
+        self.create_with(name, pet, true)
+    }
+
+    fn create_with(&self, name: &str, pet: Tamagochi, modified: bool) -> Result<Slot, SlotError> {
+        validate_name(name)?;
+        let _lock = self.lock(name)?;
+        if self.exists(name) {
+            return Err(SlotError::Exists(name.to_string()));
+        }
+        let now = now();
+        let mut slot = Slot {
+            name: name.to_string(),
+            meta: SlotMeta {
+                created: now,
+                last_played: now,
+                modified,
+                clock: ClockTrail::default(),
+            },
+            pet,
+        };
+        self.save(&mut slot)?;
+        Ok(slot)
+    }
+
+    /// Load a slot, migrating old saves and checking the signature
+    ///
+    /// Never writes: a tampered slot loaded anyway is only marked as
+    /// modified in memory, and keeps the mark once it's saved.
+    pub fn load(&self, name: &str) -> Result<Slot, SlotError> {
+        validate_name(name)?;
+        let json = match fs::read_to_string(self.path(name)) {
+            Ok(json) => json,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(SlotError::NotFound(name.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        };
+        let corrupt = |reason: String| SlotError::Corrupt(name.to_string(), reason);
+
+        let file: SlotFileIn = serde_json::from_str(&json).map_err(|e| corrupt(e.to_string()))?;
+        let pet = file.save.migrate().map_err(|e| corrupt(e.to_string()))?;
+        let mut slot = Slot {
+            name: name.to_string(),
+            meta: file.meta,
+            pet,
+        };
+
+        let body = SlotBody {
+            meta: &slot.meta,
+            save: SaveFile::new(&slot.pet),
+        };
+        let signed = file
+            .mac
+            .is_some_and(|mac| self.key.verify(&body.signed_bytes(), &mac));
+        if !signed {
+            if !self.allow_tampered {
+                return Err(SlotError::Tampered(name.to_string()));
+            }
+            slot.meta.modified = true;
+        }
+        Ok(slot)
+    }
+
+    /// Sign and write a slot to disk, recording the clocks in its trail
+    ///
+    /// Only save a pet that's up to date, after it caught up on the time
+    /// since its last save: that time is lost from the trail.
+    pub fn save(&self, slot: &mut Slot) -> Result<(), SlotError> {
+        slot.meta.clock.record(Reading::now());
+        self.write(slot)
+    }
+
+    /// Sign and write a slot to disk as it is
+    ///
+    /// The file is replaced atomically so a crash can't leave half a save.
+    fn write(&self, slot: &Slot) -> Result<(), SlotError> {
+        let body = SlotBody {
+            meta: &slot.meta,
+            save: SaveFile::new(&slot.pet),
+        };
+        let mac = self.key.sign(&body.signed_bytes());
+        let file = SlotFileOut { body, mac };
+        let json = serde_json::to_string_pretty(&file).map_err(io::Error::other)?;
+
+        let path = self.path(&slot.name);
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, json)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    /// Take a slot for this process, so that concurrent invocations
+    /// can't overwrite each other's saves
+    ///
+    /// Waits a moment for another process to finish with the slot, then
+    /// gives up with [`SlotError::Locked`]. Load the slot after locking it.
+    pub fn lock(&self, name: &str) -> Result<SlotLock, SlotError> {
+        validate_name(name)?;
+        // Lock files live apart from the slots and are never deleted, so
+        // every process locks the same file
+        let file = File::options()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(self.dir.join("locks").join(format!("{}.lock", name)))?;
+        let start = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(SlotLock { _file: file }),
+                Err(TryLockError::WouldBlock) if start.elapsed() < LOCK_TIMEOUT => {
+                    thread::sleep(Duration::from_millis(20));
+                }
+                Err(TryLockError::WouldBlock) => return Err(SlotError::Locked(name.to_string())),
+                Err(TryLockError::Error(e)) => return Err(e.into()),
+            }
+        }
+    }
+
+    /// Delete a slot
+    pub fn delete(&self, name: &str) -> Result<(), SlotError> {
+        validate_name(name)?;
+        let _lock = self.lock(name)?;
+        match fs::remove_file(self.path(name)) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(SlotError::NotFound(name.to_string()))
+            }
+            Err(e) => return Err(e.into()),
+        }
+        if self.active().as_deref() == Some(name) {
+            let _ = fs::remove_file(self.dir.join("active"));
+        }
+        Ok(())
+    }
+
+    /// Rename a slot
+    pub fn rename(&self, old: &str, new: &str) -> Result<(), SlotError> {
+        validate_name(old)?;
+        validate_name(new)?;
+        // Check under the locks, so no one creates `new` in between. Both
+        // are taken in name order, so two opposite renames can't each
+        // hold one lock and wait for the other.
+        let (first, second) = if old < new { (old, new) } else { (new, old) };
+        let _lock = (self.lock(first)?, (old != new).then(|| self.lock(second)).transpose()?);
+        if !self.exists(old) {
+            return Err(SlotError::NotFound(old.to_string()));
+        }
+        if self.exists(new) {
+            return Err(SlotError::Exists(new.to_string()));
+        }
+        // Only an existing slot counts as active, so ask before renaming
+        let was_active = self.active().as_deref() == Some(old);
+        fs::rename(self.path(old), self.path(new))?;
+        if was_active {
+            self.set_active(new)?;
+        }
+        Ok(())
+    }
+
+    /// Name of the active slot, if it still exists
+    pub fn active(&self) -> Option<String> {
+        let name = fs::read_to_string(self.dir.join("active")).ok()?;
+        let name = name.trim();
+        self.exists(name).then(|| name.to_string())
+    }
+
+    /// Make a slot the active one
+    pub fn set_active(&self, name: &str) -> Result<(), SlotError> {
+        if !self.exists(name) {
+            return Err(SlotError::NotFound(name.to_string()));
+        }
+        fs::write(self.dir.join("active"), name)?;
+        Ok(())
+    }
+}
+
+/// Slot names become file names, so keep them simple
+fn validate_name(name: &str) -> Result<(), SlotError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(SlotError::InvalidName(name.to_string()))
+    }
+}
+
+/// Default data directory
+fn data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("TAMAGOCHI_HOME") {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME") {
+        return PathBuf::from(dir).join("tamagochi");
+    }
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    home.join(".local/share/tamagochi")
+}
+
+/// Current time as Unix seconds
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format Unix seconds as `YYYY-MM-DD HH:MM` (UTC)
+pub fn format_timestamp(secs: u64) -> String {
+    let (year, month, day) = civil_date(secs);
+    let time = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60
+    )
+}
+
+/// Format Unix seconds as RFC 3339, e.g. `2024-05-01T12:30:05Z`
+pub fn format_rfc3339(secs: u64) -> String {
+    let (year, month, day) = civil_date(secs);
+    let time = secs % 86400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Year, month and day (UTC) of Unix seconds
+fn civil_date(secs: u64) -> (i64, i64, i64) {
+    let days = (secs / 86400) as i64;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A store in a fresh directory of its own
+    fn store(test: &str) -> SlotStore {
+        let dir = std::env::temp_dir().join(format!("tamagochi-slots-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        SlotStore::at(dir).unwrap()
+    }
+
+    #[test]
+    fn test_create_load_and_names() {
+        let store = store("create");
+        assert!(store.names().unwrap().is_empty());
+        store.create("pet").unwrap();
+        store.create_with("b-2", Tamagochi::new().next_generation(), false).unwrap();
+
+        assert_eq!(store.names().unwrap(), ["b-2", "pet"]);
+        assert!(matches!(store.create("pet"), Err(SlotError::Exists(_))));
+        assert_eq!(store.load("b-2").unwrap().pet.generation, 2);
+        assert!(matches!(store.load("nobody"), Err(SlotError::NotFound(_))));
+        assert_eq!(store.free_name("pet"), "pet-2");
+        assert_eq!(store.free_name("other"), "other");
+    }
+
+    #[test]
+    fn test_rename_delete_and_active() {
+        let store = store("manage");
+        store.create("a").unwrap();
+        store.create("b").unwrap();
+        assert_eq!(store.active(), None);
+        store.set_active("a").unwrap();
+        assert!(matches!(store.set_active("c"), Err(SlotError::NotFound(_))));
+
+        // The active slot follows a rename and goes away with the slot
+        assert!(matches!(store.rename("a", "b"), Err(SlotError::Exists(_))));
+        assert!(matches!(store.rename("c", "d"), Err(SlotError::NotFound(_))));
+        assert!(matches!(store.rename("a", "a"), Err(SlotError::Exists(_))));
+        store.rename("a", "c").unwrap();
+        assert_eq!(store.active().as_deref(), Some("c"));
+        assert!(!store.exists("a"));
+        store.delete("c").unwrap();
+        assert_eq!(store.active(), None);
+        assert!(matches!(store.delete("c"), Err(SlotError::NotFound(_))));
+        assert_eq!(store.names().unwrap(), ["b"]);
+    }
+
+    #[test]
+    fn test_a_locked_slot_is_refused() {
+        let store = store("lock");
+        store.create("a").unwrap();
+        store.create("b").unwrap();
+        let lock = store.lock("a").unwrap();
+
+        // Every lock opens the file anew, so this conflicts like another
+        // process would
+        assert!(matches!(store.lock("a"), Err(SlotError::Locked(name)) if name == "a"));
+        assert!(matches!(store.rename("a", "c"), Err(SlotError::Locked(_))));
+        assert!(matches!(store.rename("b", "a"), Err(SlotError::Locked(_))));
+        assert!(matches!(store.delete("a"), Err(SlotError::Locked(_))));
+        let _other = store.lock("b").unwrap();
+
+        drop(lock);
+        store.lock("a").unwrap();
+    }
+
+    /// Change a slot file on disk as a player editing it by hand would
+    fn edit(store: &SlotStore, name: &str, edit: impl FnOnce(&mut serde_json::Value)) {
+        let path = store.path(name);
+        let mut json: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        edit(&mut json);
+        fs::write(path, json.to_string()).unwrap();
+    }
+
+    #[test]
+    fn test_edited_save_is_tampered_and_marked_for_good() {
+        let store = store("tampered");
+        store.create("pet").unwrap();
+        assert!(!store.load("pet").unwrap().meta.modified);
+        edit(&store, "pet", |json| json["save"]["pet"]["care_quality"] = 100.into());
+
+        assert!(matches!(store.load("pet"), Err(SlotError::Tampered(_))));
+        let path = store.path("pet");
+        let edited = fs::read_to_string(&path).unwrap();
+        let lenient = SlotStore::at(store.dir().to_path_buf()).unwrap().allow_tampered(true);
+        let mut slot = lenient.load("pet").unwrap();
+        assert!(slot.meta.modified);
+        assert_eq!(slot.pet.care_quality, 100);
+        assert_eq!(fs::read_to_string(&path).unwrap(), edited, "loading must not write");
+
+        // Once saved, the mark is signed and the slot loads normally
+        lenient.save(&mut slot).unwrap();
+        assert!(store.load("pet").unwrap().meta.modified);
+    }
+
+    #[test]
+    fn test_unsigned_saves_are_adopted_as_modified() {
+        let store = store("unsigned");
+        store.create("old").unwrap();
+        edit(&store, "old", |json| {
+            json.as_object_mut().unwrap().remove("mac");
+            json["meta"]["clock"]["wall"] = 1000.into();
+        });
+        let dir = store.dir().to_path_buf();
+
+        // An existing key only accepts signed saves
+        let store = SlotStore::at(dir.clone()).unwrap();
+        assert!(matches!(store.load("old"), Err(SlotError::Tampered(_))));
+
+        // A new key adopts them, but can't vouch for them
+        fs::remove_file(dir.join("key")).unwrap();
+        let store = SlotStore::at(dir).unwrap();
+        let slot = store.load("old").unwrap();
+        assert!(slot.meta.modified);
+        // The time since the last save is still there to catch up on
+        assert_eq!(slot.meta.clock.wall, 1000);
+        assert_eq!(slot.meta.clock.saves, 1);
+    }
+
+    #[test]
+    fn test_imported_pets_are_modified() {
+        let store = store("import");
+        assert!(store.import("friend", Tamagochi::new()).unwrap().meta.modified);
+        assert!(store.load("friend").unwrap().meta.modified);
+    }
+
+    #[test]
+    fn test_name_validation() {
+        for name in ["pet", "My_Pet-2", &"x".repeat(MAX_NAME_LEN)] {
+            assert!(validate_name(name).is_ok(), "{}", name);
+        }
+        for name in ["", "../key", "a b", "pét", "a.json", &"x".repeat(MAX_NAME_LEN + 1)] {
+            assert!(matches!(validate_name(name), Err(SlotError::InvalidName(_))), "{}", name);
+        }
+        let store = store("names");
+        assert!(matches!(store.create("../escape"), Err(SlotError::InvalidName(_))));
+        assert!(matches!(store.load(""), Err(SlotError::InvalidName(_))));
+    }
+}
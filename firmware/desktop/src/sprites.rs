@@ -1,6 +1,7 @@
 //! ASCII Art sprites for Tamagochi display
 
-use crate::core::LifeStage;
+use crate::core::qr::QUIET_ZONE;
+use crate::core::{LifeStage, Mood, QrCode};
 
 /// Get ASCII art for a given life stage and animation frame
 pub fn get_sprite(stage: LifeStage, frame: u8) -> &'static str {
@@ -10,6 +11,56 @@ pub fn get_sprite(stage: LifeStage, frame: u8) -> &'static str {
         LifeStage::Child => get_child_sprite(frame),
         LifeStage::Teenager => get_teenager_sprite(frame),
         LifeStage::Adult(form) => get_adult_sprite(form, frame),
+        LifeStage::Elder => get_elder_sprite(frame),
+    }
+}
+
+/// Get ASCII art for a life stage showing the given mood
+///
+/// Content pets use the regular animation. Other moods swap in the mood's
+/// face on top of the stage's sprite, and older pets curl up in the
+/// sleeping sprite at night.
+pub fn get_mood_sprite(stage: LifeStage, mood: Mood, frame: u8) -> String {
+    if mood == Mood::Content {
+        return get_sprite(stage, frame).to_string();
+    }
+    if mood == Mood::Sleepy && matches!(stage, LifeStage::Adult(_) | LifeStage::Elder) {
+        return get_sleeping_sprite().to_string();
+    }
+
+    let (eyes, mouth) = mood_face(mood, frame);
+    get_sprite(stage, 0)
+        .lines()
+        .map(|line| {
+            if stage == LifeStage::Egg && line.contains('o') && line.contains('|') {
+                // Egg: the eyes peek through the shell
+                let (left, right) = eyes.split_once('.').unwrap_or((eyes, eyes));
+                let start = line.find('|').unwrap();
+                format!("{}| {} {} |", &line[..start], left, right)
+            } else if let (Some(open), Some(close)) = (line.find("( "), line.rfind(" )")) {
+                format!("{}( {} ){}", &line[..open], eyes, &line[close + 2..])
+            } else if let (Some(open), Some(close)) = (line.find("> "), line.rfind(" <")) {
+                format!("{}> {} <{}", &line[..open], mouth, &line[close + 2..])
+            } else {
+                line.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Eyes and mouth for a mood
+fn mood_face(mood: Mood, frame: u8) -> (&'static str, &'static str) {
+    let even = frame.is_multiple_of(2);
+    match mood {
+        Mood::Sick => (if even { "@.@" } else { "x.x" }, "~"),
+        Mood::Angry => (">.<", if even { "#" } else { "!" }),
+        Mood::Hungry => ("o.o", if even { "O" } else { "o" }),
+        Mood::Sad => ("T.T", "n"),
+        Mood::Sleepy => ("-.-", if even { "z" } else { "Z" }),
+        Mood::Bored => ("=.=", "-"),
+        Mood::Happy => ("^.^", if even { "w" } else { "v" }),
+        Mood::Content => ("o.o", "^"),
     }
 }
 
@@ -32,6 +83,59 @@ fn get_egg_sprite(frame: u8) -> &'static str {
     }
 }
 
+/// Get a wiggling egg sprite (the egg wants to be warmed)
+pub fn get_wiggle_sprite(frame: u8) -> &'static str {
+    match frame % 2 {
+        0 => r#"
+   ___
+  /   \  ~
+ |  o  |
+ |     |
+  \___/
+"#,
+        _ => r#"
+     ___
+ ~  /   \
+   |  o  |
+   |     |
+    \___/
+"#,
+    }
+}
+
+/// Get the hatching animation, played in order
+pub fn get_hatching_frames() -> [&'static str; 4] {
+    [
+        r#"
+    ___
+   /   \
+  |  o  |
+  |  /  |
+   \___/
+"#,
+        r#"
+    ___
+   / / \
+  | /o  |
+  | \/\ |
+   \___/
+"#,
+        r#"
+   _/\_
+  /    \
+     o
+  |\/\/\|
+   \___/
+"#,
+        r#"
+   /\_/\
+  ( o.o )
+  |\/\/\|
+   \___/
+"#,
+    ]
+}
+
 fn get_baby_sprite(frame: u8) -> &'static str {
     match frame % 3 {
         0 => r#"
@@ -262,6 +366,41 @@ fn get_premium_adult(frame: u8) -> &'static str {
     }
 }
 
+fn get_elder_sprite(frame: u8) -> &'static str {
+    match frame % 3 {
+        0 => r#"
+      /\_/\
+     ( -.- )
+      > ~ <
+     /|   |\
+    / |___| \
+   /  |   |  \ |
+  /___|   |___\|
+     |___|     |
+"#,
+        1 => r#"
+      /\_/\
+     ( o.- )
+      > ~ <
+     /|   |\
+    / |___| \
+   /  |   |  \ |
+  /___|   |___\|
+     |___|     |
+"#,
+        _ => r#"
+      /\_/\
+     ( -.- )
+      > o <
+     /|   |\
+    / |___| \
+   /  |   |  \ |
+  /___|   |___\|
+     |___|     |
+"#,
+    }
+}
+
 /// Get death sprite
 pub fn get_death_sprite() -> &'static str {
     r#"
@@ -277,6 +416,20 @@ pub fn get_death_sprite() -> &'static str {
 "#
 }
 
+/// Get passed-away sprite (natural death of old age)
+pub fn get_passed_away_sprite() -> &'static str {
+    r#"
+       .---.
+      (     )
+      /\_/\
+     ( ^.^ )
+      > ~ <
+     /|   |\
+    / |___| \
+     Farewell
+"#
+}
+
 /// Get sleeping sprite (for idle states)
 pub fn get_sleeping_sprite() -> &'static str {
     r#"
@@ -289,3 +442,32 @@ pub fn get_sleeping_sprite() -> &'static str {
   /_________\
 "#
 }
+
+/// Render a QR code with Unicode half blocks, two modules per character
+///
+/// Colours are forced to black on white so the code scans on dark
+/// terminal themes too.
+pub fn get_qr_sprite(qr: &QrCode) -> String {
+    let dark = |x: isize, y: isize| {
+        x >= 0 && y >= 0 && qr.module(x as usize, y as usize)
+    };
+    let margin = QUIET_ZONE as isize;
+    let size = qr.size() as isize;
+
+    let mut out = String::new();
+    let mut y = -margin;
+    while y < size + margin {
+        out.push_str("\x1b[30;47m");
+        for x in -margin..size + margin {
+            out.push(match (dark(x, y), dark(x, y + 1)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            });
+        }
+        out.push_str("\x1b[0m\n");
+        y += 2;
+    }
+    out
+}
@@ -25,7 +25,6 @@ use embedded_graphics::{
     mono_font::{ascii::FONT_6X10, MonoTextStyle},
     pixelcolor::BinaryColor,
     prelude::*,
-    primitives::{Circle, PrimitiveStyle, Rectangle},
     text::Text,
 };
 
@@ -34,9 +33,11 @@ use ssd1306::{
     I2CDisplayInterface, Ssd1306,
 };
 
-use tamagochi_core::{Tamagochi, GameEvent};
+use tamagochi_core::minigame::Input;
+use tamagochi_core::{Action, GameEvent, GameSession, LifeStage, Listener, MiniGame, MiniGameKind, Tamagochi};
+use tamagochi_graphics::screen::{self, action_label};
+use tamagochi_graphics::sprites;
 
-mod sprites;
 
 fn main() {
     // Initialize ESP-IDF
@@ -79,15 +80,18 @@ fn main() {
 
     log::info!("Buttons initialized");
 
-    // Initialize Tamagochi game state
-    let mut tama = Tamagochi::new();
-    let mut frame: u8 = 0;
-    let mut last_button_state = [false; 4];
-
     // Show welcome screen
     draw_welcome(&mut display);
     FreeRtos::delay_ms(2000);
 
+    // Initialize Tamagochi game state, shown on the display as it changes
+    let mut session = GameSession::new(Tamagochi::new(), Screen { display });
+    let mut frame: u8 = 0;
+    let mut last_button_state = [false; 4];
+    let mut minigame: Option<MiniGame> = None;
+    let mut games_played: u8 = 0;
+    let mut showing_code = false;
+
     log::info!("Starting main game loop");
 
     // Main game loop
@@ -100,44 +104,88 @@ fn main() {
             btn_clean.is_low(),
         ];
 
+        let now_ms = millis();
+
+        // Holding FEED and CLEAN together shows the passcode as a QR code
+        if btn_states[0] && btn_states[3] && !showing_code && minigame.is_none() {
+            log::info!("Showing passcode");
+            showing_code = true;
+            last_button_state = btn_states;
+            let passcode = session.tama().to_passcode();
+            let display = &mut session.listener_mut().display;
+            display.clear_buffer();
+            sprites::draw_passcode(display, &passcode);
+            display.flush().ok();
+        }
+
         // Handle button presses (detect rising edge)
         for (i, (&current, &last)) in btn_states.iter().zip(last_button_state.iter()).enumerate() {
             if current && !last {
                 // Button was just pressed
-                match i {
-                    0 => {
-                        log::info!("Button: FEED");
-                        tama.feed();
-                        show_action_feedback(&mut display, "FEED!");
-                    }
-                    1 => {
-                        log::info!("Button: PLAY");
-                        tama.play();
-                        show_action_feedback(&mut display, "PLAY!");
-                    }
-                    2 => {
-                        log::info!("Button: MEDICINE");
-                        tama.medicine();
-                        show_action_feedback(&mut display, "MEDICINE!");
-                    }
-                    3 => {
-                        log::info!("Button: CLEAN");
-                        tama.clean();
-                        show_action_feedback(&mut display, "CLEAN!");
-                    }
-                    _ => {}
+                if showing_code {
+                    // Any button closes the passcode screen
+                    showing_code = false;
+                    continue;
+                }
+                if let Some(game) = minigame.as_mut() {
+                    // Outer buttons pick a side, the middle ones press
+                    let input = match i {
+                        0 => Input::Left,
+                        3 => Input::Right,
+                        _ => Input::Press,
+                    };
+                    game.input(input, now_ms);
+                    continue;
+                }
+
+                // Any button warms an egg
+                let action = if session.tama().stage == LifeStage::Egg {
+                    Action::Warm
+                } else {
+                    Action::BUTTONS[i]
+                };
+                log::info!("Button: {:?}", action);
+                if action == Action::Play && session.tama().can_perform(action) {
+                    // Alternate between the two games
+                    let kind = if games_played % 2 == 0 {
+                        MiniGameKind::Guess
+                    } else {
+                        MiniGameKind::Reaction
+                    };
+                    games_played = games_played.wrapping_add(1);
+                    minigame = Some(MiniGame::new(kind, now_ms ^ session.tama().age, now_ms));
+                } else {
+                    session.perform(action);
                 }
             }
         }
         last_button_state = btn_states;
 
-        // Update game state
-        if let Some(event) = tama.update() {
-            handle_event(&mut display, event);
+        // Advance a running mini-game and apply its result once finished
+        if let Some(game) = minigame.as_mut() {
+            game.update(now_ms);
+            if let Some(result) = game.result() {
+                log::info!("Mini-game won {}/{}", result.wins, result.rounds);
+                session.apply_minigame(result);
+                minigame = None;
+            }
         }
 
+        // Update game state
+        session.update();
+
         // Render current state to display
-        draw_game_state(&mut display, &tama, frame);
+        let tama = *session.tama();
+        let display = &mut session.listener_mut().display;
+        match &minigame {
+            Some(game) => {
+                display.clear_buffer();
+                sprites::draw_minigame(display, game, tama.stage, frame);
+                display.flush().ok();
+            }
+            None if showing_code => {}
+            None => draw_game_state(display, &tama, frame),
+        }
 
         // Increment animation frame
         frame = frame.wrapping_add(1);
@@ -147,6 +195,12 @@ fn main() {
     }
 }
 
+/// Milliseconds since boot, for mini-game timing
+fn millis() -> u32 {
+    // SAFETY: esp_timer_get_time only reads the high-resolution timer
+    (unsafe { esp_idf_svc::sys::esp_timer_get_time() } / 1000) as u32
+}
+
 /// Draw welcome screen
 fn draw_welcome(display: &mut impl DrawTarget<Color = BinaryColor>) {
     display.clear_buffer();
@@ -173,87 +227,10 @@ fn draw_game_state(
     frame: u8,
 ) {
     display.clear_buffer();
-
-    // Draw sprite in center-top area
-    sprites::draw_sprite(display, tama.stage, frame, Point::new(40, 5));
-
-    // Draw status bars at bottom
-    draw_status_bars(display, tama);
-
-    // Draw warning indicators if needed
-    draw_warnings(display, tama);
-
+    screen::draw_game_state(display, tama, frame);
     display.flush().ok();
 }
 
-/// Draw status bars for hunger, happiness, health
-fn draw_status_bars(display: &mut impl DrawTarget<Color = BinaryColor>, tama: &Tamagochi) {
-    let bar_width = 40;
-    let bar_height = 4;
-    let start_y = 52;
-    
-    // Hunger bar (0 = empty, 100 = full, so invert for display)
-    let hunger_fill = ((100 - tama.hunger) as i32 * bar_width) / 100;
-    draw_bar(display, Point::new(0, start_y), bar_width, bar_height, hunger_fill);
-    
-    // Happiness bar
-    let happy_fill = (tama.happiness as i32 * bar_width) / 100;
-    draw_bar(display, Point::new(44, start_y), bar_width, bar_height, happy_fill);
-    
-    // Health bar
-    let health_fill = (tama.health as i32 * bar_width) / 100;
-    draw_bar(display, Point::new(88, start_y), bar_width, bar_height, health_fill);
-    
-    // Labels
-    let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
-    Text::new("F", Point::new(0, 63), text_style).draw(display).ok();
-    Text::new("H", Point::new(44, 63), text_style).draw(display).ok();
-    Text::new("M", Point::new(88, 63), text_style).draw(display).ok();
-}
-
-/// Draw a single status bar
-fn draw_bar(
-    display: &mut impl DrawTarget<Color = BinaryColor>,
-    position: Point,
-    width: i32,
-    height: i32,
-    fill: i32,
-) {
-    // Draw outline
-    Rectangle::new(position, Size::new(width as u32, height as u32))
-        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
-        .draw(display)
-        .ok();
-    
-    // Draw filled portion
-    if fill > 0 {
-        Rectangle::new(
-            position + Point::new(1, 1),
-            Size::new((fill - 2).max(0) as u32, (height - 2) as u32),
-        )
-        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
-        .draw(display)
-        .ok();
-    }
-}
-
-/// Draw warning indicators
-fn draw_warnings(display: &mut impl DrawTarget<Color = BinaryColor>, tama: &Tamagochi) {
-    let needs = tama.needs_status();
-    let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
-    
-    let mut y = 42;
-    if needs.hunger_critical {
-        Text::new("HUNGRY!", Point::new(2, y), text_style).draw(display).ok();
-    }
-    if needs.happiness_critical {
-        Text::new("SAD!", Point::new(50, y), text_style).draw(display).ok();
-    }
-    if needs.health_critical {
-        Text::new("SICK!", Point::new(90, y), text_style).draw(display).ok();
-    }
-}
-
 /// Show brief action feedback
 fn show_action_feedback(display: &mut impl DrawTarget<Color = BinaryColor>, text: &str) {
     display.clear_buffer();
@@ -267,9 +244,40 @@ fn show_action_feedback(display: &mut impl DrawTarget<Color = BinaryColor>, text
     FreeRtos::delay_ms(300);
 }
 
+/// Shows what happens to the pet on the display
+struct Screen<D> {
+    display: D,
+}
+
+impl<D: DrawTarget<Color = BinaryColor>> Listener for Screen<D> {
+    fn on_event(&mut self, event: GameEvent, _tama: &Tamagochi) {
+        handle_event(&mut self.display, event);
+    }
+
+    fn on_action(&mut self, action: Action, performed: bool, _tama: &Tamagochi) {
+        let text = if performed { action_label(action) } else { "NOT NOW" };
+        show_action_feedback(&mut self.display, text);
+    }
+}
+
 /// Handle game events
 fn handle_event(display: &mut impl DrawTarget<Color = BinaryColor>, event: GameEvent) {
     match event {
+        GameEvent::Hatched => {
+            log::info!("Egg hatched");
+            for step in 0..sprites::HATCHING_STEPS {
+                display.clear_buffer();
+                sprites::draw_hatching(display, step, Point::new(40, 5));
+                display.flush().ok();
+                FreeRtos::delay_ms(500);
+            }
+            let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+            Text::new("HATCHED!", Point::new(40, 55), text_style)
+                .draw(display)
+                .ok();
+            display.flush().ok();
+            FreeRtos::delay_ms(1500);
+        }
         GameEvent::Evolution(stage) => {
             log::info!("Evolution: {:?}", stage);
             display.clear_buffer();
@@ -293,6 +301,22 @@ fn handle_event(display: &mut impl DrawTarget<Color = BinaryColor>, event: GameE
                 FreeRtos::delay_ms(1000);
             }
         }
+        GameEvent::PassedAway => {
+            log::info!("Tamagochi passed away of old age");
+            display.clear_buffer();
+            let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+            Text::new("FAREWELL", Point::new(40, 28), text_style)
+                .draw(display)
+                .ok();
+            Text::new("A long life", Point::new(31, 42), text_style)
+                .draw(display)
+                .ok();
+            display.flush().ok();
+            // Stay on farewell screen
+            loop {
+                FreeRtos::delay_ms(1000);
+            }
+        }
         GameEvent::NeedsAttention => {
             log::info!("Needs attention");
             // Visual indicator already shown by draw_warnings
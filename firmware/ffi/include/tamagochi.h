@@ -0,0 +1,191 @@
+/* Generated by cbindgen from firmware/ffi; don't edit by hand. */
+
+#ifndef TAMAGOCHI_H
+#define TAMAGOCHI_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Size of a save in bytes, the buffer `tama_serialize` needs
+#define TAMA_SAVE_SIZE 36
+
+// Bytes of memory `tama_init` needs for a pet, with room for the pet to
+// grow in later versions
+#define TAMA_HANDLE_SIZE 64
+
+// Alignment `tama_init` needs; an array of `uint64_t` has it
+#define TAMA_HANDLE_ALIGN 8
+
+// What `tama_update` reports
+typedef enum TamaEvent {
+  // Nothing happened this tick
+  TAMA_EVENT_NONE,
+  // The egg hatched into a baby
+  TAMA_EVENT_HATCHED,
+  // The pet reached a new stage, see `TamaStatus::stage`
+  TAMA_EVENT_EVOLUTION,
+  // The pet died from neglect
+  TAMA_EVENT_DEATH,
+  // The pet passed away of old age
+  TAMA_EVENT_PASSED_AWAY,
+  // Hunger, happiness or health is critical
+  TAMA_EVENT_NEEDS_ATTENTION,
+  // A day older, see `TamaStatus::age_days`
+  TAMA_EVENT_BIRTHDAY,
+} TamaEvent;
+
+// Life stage, with the three adult forms told apart
+typedef enum TamaStage {
+  TAMA_STAGE_EGG,
+  TAMA_STAGE_BABY,
+  TAMA_STAGE_CHILD,
+  TAMA_STAGE_TEENAGER,
+  TAMA_STAGE_ADULT_NEGLECTED,
+  TAMA_STAGE_ADULT_NORMAL,
+  TAMA_STAGE_ADULT_PREMIUM,
+  TAMA_STAGE_ELDER,
+} TamaStage;
+
+// How the pet feels, from most to least urgent
+typedef enum TamaMood {
+  TAMA_MOOD_SICK,
+  TAMA_MOOD_ANGRY,
+  TAMA_MOOD_HUNGRY,
+  TAMA_MOOD_SAD,
+  TAMA_MOOD_SLEEPY,
+  TAMA_MOOD_BORED,
+  TAMA_MOOD_CONTENT,
+  TAMA_MOOD_HAPPY,
+} TamaMood;
+
+// A pet; only ever used through a pointer
+typedef struct Tama Tama;
+
+// Everything about the pet at a glance, filled in by `tama_status`
+typedef struct TamaStatus {
+  bool alive;
+  enum TamaStage stage;
+  enum TamaMood mood;
+  uint32_t age_days;
+  // 0 = full, 100 = starving
+  uint8_t hunger;
+  uint8_t happiness;
+  uint8_t health;
+  uint8_t care_rating;
+  // Grams
+  uint8_t weight;
+  bool hunger_critical;
+  bool happiness_critical;
+  bool health_critical;
+} TamaStatus;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Create a fresh egg; free it with `tama_free` (`std` feature)
+struct Tama *tama_new(void);
+
+// Free a pet; NULL is ignored (`std` feature)
+//
+// # Safety
+//
+// `tama` must come from `tama_new` or `tama_deserialize` and not be used
+// afterwards.
+void tama_free(struct Tama *tama);
+
+// Create a fresh egg in `buf`, returning it as a pet, or NULL if `buf`
+// is NULL, shorter than `TAMA_HANDLE_SIZE` or not aligned to
+// `TAMA_HANDLE_ALIGN`
+//
+// The pet lives as long as the memory; it needs no freeing.
+//
+// # Safety
+//
+// `buf` must be writable for `len` bytes or NULL, and not be used for
+// anything else while the pet is.
+struct Tama *tama_init(void *buf, size_t len);
+
+// Feed the pet; false if it can't eat right now
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL.
+bool tama_feed(struct Tama *tama);
+
+// Play with the pet; false if it can't play right now
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL.
+bool tama_play(struct Tama *tama);
+
+// Give medicine; false if it can't take any right now
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL.
+bool tama_medicine(struct Tama *tama);
+
+// Clean up after the pet; false if there's nothing to clean
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL.
+bool tama_clean(struct Tama *tama);
+
+// Warm the egg; false if it isn't an egg
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL.
+bool tama_warm(struct Tama *tama);
+
+// Run one tick, a second of pet time; call it once a second
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL.
+enum TamaEvent tama_update(struct Tama *tama);
+
+// Fill in `out` with the pet's status; false if either is NULL
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL, `out` writable or NULL.
+bool tama_status(const struct Tama *tama, struct TamaStatus *out);
+
+// Write the pet's save into `buf`, returning the bytes written
+// (`TAMA_SAVE_SIZE`), or 0 if `len` is too small or a pointer is NULL
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL, `buf` writable for `len` bytes or
+// NULL.
+size_t tama_serialize(const struct Tama *tama, uint8_t *buf, size_t len);
+
+// Load a pet from a save; NULL if the save is damaged or from a newer
+// version. Free it with `tama_free` (`std` feature).
+//
+// # Safety
+//
+// `buf` must be readable for `len` bytes, or NULL.
+struct Tama *tama_deserialize(const uint8_t *buf, size_t len);
+
+// Replace the pet with one loaded from a save; false, leaving the pet
+// as it was, if the save is damaged or from a newer version
+//
+// # Safety
+//
+// `tama` must be a live pet or NULL, `buf` readable for `len` bytes or
+// NULL.
+bool tama_load(struct Tama *tama, const uint8_t *buf, size_t len);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* TAMAGOCHI_H */
@@ -0,0 +1,366 @@
+//! C bindings for the core
+//!
+//! Builds the game logic as a static or shared library with a small
+//! handle-based API, so firmware written in C can run the same pet:
+//!
+//! ```c
+//! #include "tamagochi.h"
+//!
+//! Tama *pet = tama_new();
+//! tama_warm(pet);
+//! for (;;) {                      // once a second
+//!     TamaEvent event = tama_update(pet);
+//!     if (event == TAMA_EVENT_HATCHED) play_sound();
+//!
+//!     TamaStatus status;
+//!     tama_status(pet, &status);
+//!     draw(status.hunger, status.happiness, status.health);
+//! }
+//!
+//! uint8_t save[TAMA_SAVE_SIZE];
+//! tama_serialize(pet, save, sizeof save);
+//! tama_free(pet);
+//! ```
+//!
+//! Firmware without a heap keeps the pet in memory of its own instead:
+//!
+//! ```c
+//! static uint64_t storage[TAMA_HANDLE_SIZE / sizeof(uint64_t)];
+//! Tama *pet = tama_init(storage, sizeof storage);
+//! tama_load(pet, save, sizeof save);   // or keep the fresh egg
+//! ```
+//!
+//! The crate is `no_std`. `tama_new`, `tama_free` and `tama_deserialize`
+//! allocate, so they come with the default `std` feature, which brings
+//! the global allocator. Without `std`, the library brings its own panic
+//! handler, which stops in an endless loop, e.g. for
+//! `cargo build --target thumbv7em-none-eabi --no-default-features`.
+//!
+//! The header is `include/tamagochi.h`, generated with cbindgen. Saves
+//! are the binary format of [`tamagochi_core::save`], so a pet moves
+//! freely between a C device, the ESP32 and the desktop.
+
+#![no_std]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+use core::ffi::c_void;
+use core::{mem, ptr, slice};
+#[cfg(feature = "std")]
+use std::boxed::Box;
+
+use tamagochi_core::{save, Action, AdultForm, GameEvent, LifeStage, Mood, Tamagochi};
+
+/// Size of a save in bytes, the buffer `tama_serialize` needs
+pub const TAMA_SAVE_SIZE: usize = 36;
+
+const _: () = assert!(TAMA_SAVE_SIZE == save::SAVE_SIZE);
+
+/// Bytes of memory `tama_init` needs for a pet, with room for the pet to
+/// grow in later versions
+pub const TAMA_HANDLE_SIZE: usize = 64;
+
+/// Alignment `tama_init` needs; an array of `uint64_t` has it
+pub const TAMA_HANDLE_ALIGN: usize = 8;
+
+const _: () = assert!(mem::size_of::<Tama>() <= TAMA_HANDLE_SIZE);
+const _: () = assert!(mem::align_of::<Tama>() <= TAMA_HANDLE_ALIGN);
+
+/// A pet; only ever used through a pointer
+pub struct Tama(Tamagochi);
+
+#[cfg(not(feature = "std"))]
+#[panic_handler]
+fn panic(_info: &core::panic::PanicInfo) -> ! {
+    // Nothing in the API panics; there is no one to tell on bare metal
+    loop {}
+}
+
+/// What `tama_update` reports
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TamaEvent {
+    /// Nothing happened this tick
+    None,
+    /// The egg hatched into a baby
+    Hatched,
+    /// The pet reached a new stage, see `TamaStatus::stage`
+    Evolution,
+    /// The pet died from neglect
+    Death,
+    /// The pet passed away of old age
+    PassedAway,
+    /// Hunger, happiness or health is critical
+    NeedsAttention,
+    /// A day older, see `TamaStatus::age_days`
+    Birthday,
+}
+
+/// Life stage, with the three adult forms told apart
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TamaStage {
+    Egg,
+    Baby,
+    Child,
+    Teenager,
+    AdultNeglected,
+    AdultNormal,
+    AdultPremium,
+    Elder,
+}
+
+/// How the pet feels, from most to least urgent
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TamaMood {
+    Sick,
+    Angry,
+    Hungry,
+    Sad,
+    Sleepy,
+    Bored,
+    Content,
+    Happy,
+}
+
+/// Everything about the pet at a glance, filled in by `tama_status`
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct TamaStatus {
+    pub alive: bool,
+    pub stage: TamaStage,
+    pub mood: TamaMood,
+    pub age_days: u32,
+    /// 0 = full, 100 = starving
+    pub hunger: u8,
+    pub happiness: u8,
+    pub health: u8,
+    pub care_rating: u8,
+    /// Grams
+    pub weight: u8,
+    pub hunger_critical: bool,
+    pub happiness_critical: bool,
+    pub health_critical: bool,
+}
+
+/// Create a fresh egg; free it with `tama_free` (`std` feature)
+#[cfg(feature = "std")]
+#[no_mangle]
+pub extern "C" fn tama_new() -> *mut Tama {
+    Box::into_raw(Box::new(Tama(Tamagochi::new())))
+}
+
+/// Free a pet; NULL is ignored (`std` feature)
+///
+/// # Safety
+///
+/// `tama` must come from `tama_new` or `tama_deserialize` and not be used
+/// afterwards.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn tama_free(tama: *mut Tama) {
+    if !tama.is_null() {
+        drop(Box::from_raw(tama));
+    }
+}
+
+/// Create a fresh egg in `buf`, returning it as a pet, or NULL if `buf`
+/// is NULL, shorter than `TAMA_HANDLE_SIZE` or not aligned to
+/// `TAMA_HANDLE_ALIGN`
+///
+/// The pet lives as long as the memory; it needs no freeing.
+///
+/// # Safety
+///
+/// `buf` must be writable for `len` bytes or NULL, and not be used for
+/// anything else while the pet is.
+#[no_mangle]
+pub unsafe extern "C" fn tama_init(buf: *mut c_void, len: usize) -> *mut Tama {
+    if buf.is_null() || len < TAMA_HANDLE_SIZE || !(buf as usize).is_multiple_of(TAMA_HANDLE_ALIGN) {
+        return ptr::null_mut();
+    }
+    let tama = buf.cast::<Tama>();
+    tama.write(Tama(Tamagochi::new()));
+    tama
+}
+
+/// Feed the pet; false if it can't eat right now
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_feed(tama: *mut Tama) -> bool {
+    perform(tama, Action::Feed)
+}
+
+/// Play with the pet; false if it can't play right now
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_play(tama: *mut Tama) -> bool {
+    perform(tama, Action::Play)
+}
+
+/// Give medicine; false if it can't take any right now
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_medicine(tama: *mut Tama) -> bool {
+    perform(tama, Action::Medicine)
+}
+
+/// Clean up after the pet; false if there's nothing to clean
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_clean(tama: *mut Tama) -> bool {
+    perform(tama, Action::Clean)
+}
+
+/// Warm the egg; false if it isn't an egg
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_warm(tama: *mut Tama) -> bool {
+    perform(tama, Action::Warm)
+}
+
+/// Run one tick, a second of pet time; call it once a second
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_update(tama: *mut Tama) -> TamaEvent {
+    match tama.as_mut().and_then(|tama| tama.0.update()) {
+        None => TamaEvent::None,
+        Some(GameEvent::Hatched) => TamaEvent::Hatched,
+        Some(GameEvent::Evolution(_)) => TamaEvent::Evolution,
+        Some(GameEvent::Death) => TamaEvent::Death,
+        Some(GameEvent::PassedAway) => TamaEvent::PassedAway,
+        Some(GameEvent::NeedsAttention) => TamaEvent::NeedsAttention,
+        Some(GameEvent::Birthday(_)) => TamaEvent::Birthday,
+    }
+}
+
+/// Fill in `out` with the pet's status; false if either is NULL
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL, `out` writable or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_status(tama: *const Tama, out: *mut TamaStatus) -> bool {
+    let (Some(tama), Some(out)) = (tama.as_ref(), out.as_mut()) else {
+        return false;
+    };
+    *out = status(&tama.0);
+    true
+}
+
+/// Write the pet's save into `buf`, returning the bytes written
+/// (`TAMA_SAVE_SIZE`), or 0 if `len` is too small or a pointer is NULL
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL, `buf` writable for `len` bytes or
+/// NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_serialize(tama: *const Tama, buf: *mut u8, len: usize) -> usize {
+    let Some(tama) = tama.as_ref() else {
+        return 0;
+    };
+    if buf.is_null() {
+        return 0;
+    }
+    save::encode(&tama.0, slice::from_raw_parts_mut(buf, len)).unwrap_or(0)
+}
+
+/// Load a pet from a save; NULL if the save is damaged or from a newer
+/// version. Free it with `tama_free` (`std` feature).
+///
+/// # Safety
+///
+/// `buf` must be readable for `len` bytes, or NULL.
+#[cfg(feature = "std")]
+#[no_mangle]
+pub unsafe extern "C" fn tama_deserialize(buf: *const u8, len: usize) -> *mut Tama {
+    match load(buf, len) {
+        Some(pet) => Box::into_raw(Box::new(Tama(pet))),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Replace the pet with one loaded from a save; false, leaving the pet
+/// as it was, if the save is damaged or from a newer version
+///
+/// # Safety
+///
+/// `tama` must be a live pet or NULL, `buf` readable for `len` bytes or
+/// NULL.
+#[no_mangle]
+pub unsafe extern "C" fn tama_load(tama: *mut Tama, buf: *const u8, len: usize) -> bool {
+    let (Some(tama), Some(pet)) = (tama.as_mut(), load(buf, len)) else {
+        return false;
+    };
+    tama.0 = pet;
+    true
+}
+
+unsafe fn load(buf: *const u8, len: usize) -> Option<Tamagochi> {
+    if buf.is_null() {
+        return None;
+    }
+    Tamagochi::from_bytes(slice::from_raw_parts(buf, len)).ok()
+}
+
+unsafe fn perform(tama: *mut Tama, action: Action) -> bool {
+    tama.as_mut().is_some_and(|tama| tama.0.perform(action))
+}
+
+fn status(pet: &Tamagochi) -> TamaStatus {
+    let summary = pet.status_summary();
+    let needs = pet.needs_status();
+    TamaStatus {
+        alive: summary.alive,
+        stage: match summary.stage {
+            LifeStage::Egg => TamaStage::Egg,
+            LifeStage::Baby => TamaStage::Baby,
+            LifeStage::Child => TamaStage::Child,
+            LifeStage::Teenager => TamaStage::Teenager,
+            LifeStage::Adult(AdultForm::Neglected) => TamaStage::AdultNeglected,
+            LifeStage::Adult(AdultForm::Normal) => TamaStage::AdultNormal,
+            LifeStage::Adult(AdultForm::Premium) => TamaStage::AdultPremium,
+            LifeStage::Elder => TamaStage::Elder,
+        },
+        mood: match summary.mood {
+            Mood::Sick => TamaMood::Sick,
+            Mood::Angry => TamaMood::Angry,
+            Mood::Hungry => TamaMood::Hungry,
+            Mood::Sad => TamaMood::Sad,
+            Mood::Sleepy => TamaMood::Sleepy,
+            Mood::Bored => TamaMood::Bored,
+            Mood::Content => TamaMood::Content,
+            Mood::Happy => TamaMood::Happy,
+        },
+        age_days: summary.age_days,
+        hunger: summary.hunger_level,
+        happiness: summary.happiness_level,
+        health: summary.health_level,
+        care_rating: summary.care_rating,
+        weight: summary.weight,
+        hunger_critical: needs.hunger_critical,
+        happiness_critical: needs.happiness_critical,
+        health_critical: needs.health_critical,
+    }
+}
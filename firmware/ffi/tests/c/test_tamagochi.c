@@ -0,0 +1,74 @@
+/* Drives a pet through the C API; exits non-zero at the first failed check */
+
+#include <stdio.h>
+
+#include "tamagochi.h"
+
+#define CHECK(cond)                                                              \
+    do {                                                                         \
+        if (!(cond)) {                                                           \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                            \
+        }                                                                        \
+    } while (0)
+
+int main(void) {
+    Tama *pet = tama_new();
+    CHECK(pet != NULL);
+
+    TamaStatus status;
+    CHECK(tama_status(pet, &status));
+    CHECK(status.alive);
+    CHECK(status.stage == TAMA_STAGE_EGG);
+    CHECK(!tama_feed(pet)); /* an egg can't eat */
+
+    int hatched = 0;
+    for (int tick = 0; tick < 7200 && !hatched; tick++) {
+        if (tick % 10 == 0) {
+            CHECK(tama_warm(pet));
+        }
+        hatched = tama_update(pet) == TAMA_EVENT_HATCHED;
+    }
+    CHECK(hatched);
+    CHECK(tama_feed(pet));
+    CHECK(tama_status(pet, &status));
+    CHECK(status.stage == TAMA_STAGE_BABY);
+
+    uint8_t save[TAMA_SAVE_SIZE];
+    CHECK(tama_serialize(pet, save, sizeof save - 1) == 0);
+    CHECK(tama_serialize(pet, save, sizeof save) == TAMA_SAVE_SIZE);
+    Tama *copy = tama_deserialize(save, sizeof save);
+    CHECK(copy != NULL);
+    TamaStatus copied;
+    CHECK(tama_status(copy, &copied));
+    CHECK(copied.stage == status.stage);
+    CHECK(copied.hunger == status.hunger);
+    CHECK(copied.weight == status.weight);
+
+    /* The same without a heap, in memory of our own */
+    static uint64_t storage[TAMA_HANDLE_SIZE / sizeof(uint64_t)];
+    CHECK(tama_init(storage, sizeof storage - 1) == NULL);
+    CHECK(tama_init((uint8_t *)storage + 1, sizeof storage - 1) == NULL);
+    Tama *kept = tama_init(storage, sizeof storage);
+    CHECK(kept != NULL);
+    CHECK(tama_status(kept, &copied));
+    CHECK(copied.stage == TAMA_STAGE_EGG);
+    CHECK(tama_load(kept, save, sizeof save));
+    CHECK(tama_status(kept, &copied));
+    CHECK(copied.stage == status.stage);
+    CHECK(copied.hunger == status.hunger);
+
+    save[10] ^= 1;
+    CHECK(tama_deserialize(save, sizeof save) == NULL);
+    CHECK(!tama_load(kept, save, sizeof save));
+    CHECK(tama_status(kept, &copied));
+    CHECK(copied.stage == status.stage);
+
+    CHECK(tama_update(NULL) == TAMA_EVENT_NONE);
+    CHECK(!tama_status(pet, NULL));
+    tama_free(copy);
+    tama_free(pet);
+    tama_free(NULL);
+    puts("ok");
+    return 0;
+}
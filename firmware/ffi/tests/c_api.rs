@@ -0,0 +1,50 @@
+//! The C API as C sees it: the committed header is current, and a C
+//! program using it compiles, links against the static library and runs
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+fn crate_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn test_committed_header_is_current() {
+    // Regenerate with `cbindgen --config cbindgen.toml --output include/tamagochi.h`
+    let config = cbindgen::Config::from_file(crate_dir().join("cbindgen.toml")).unwrap();
+    let bindings = cbindgen::Builder::new()
+        .with_config(config)
+        .with_src(crate_dir().join("src/lib.rs"))
+        .generate()
+        .unwrap();
+    let mut header = Vec::new();
+    bindings.write(&mut header);
+    let committed = fs::read_to_string(crate_dir().join("include/tamagochi.h")).unwrap();
+    assert_eq!(String::from_utf8(header).unwrap(), committed, "include/tamagochi.h is out of date");
+}
+
+#[test]
+fn test_c_program() {
+    // Cargo builds the static library next to this test in target/<profile>/deps
+    let exe = std::env::current_exe().unwrap();
+    let lib = exe.with_file_name("libtamagochi_ffi.a");
+    let program = std::env::temp_dir().join(format!("tamagochi-ffi-{}", std::process::id()));
+
+    let cc = std::env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(cc)
+        .args(["-std=c99", "-Wall", "-Werror", "-I"])
+        .arg(crate_dir().join("include"))
+        .arg(crate_dir().join("tests/c/test_tamagochi.c"))
+        .arg(&lib)
+        .args(["-lpthread", "-ldl", "-lm", "-o"])
+        .arg(&program)
+        .status()
+        .unwrap();
+    assert!(status.success(), "compiling the C program failed");
+
+    let output = Command::new(&program).output().unwrap();
+    fs::remove_file(&program).unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "ok\n");
+}
@@ -0,0 +1,101 @@
+//! In-memory display for rendering off-device
+
+use core::convert::Infallible;
+
+use embedded_graphics::{pixelcolor::BinaryColor, prelude::*};
+
+/// Display width in pixels
+pub const WIDTH: u32 = 128;
+/// Display height in pixels
+pub const HEIGHT: u32 = 64;
+
+/// A 128x64 one-bit frame, one bit per pixel in row-major order
+#[derive(Clone, PartialEq, Eq)]
+pub struct Framebuffer {
+    bits: [u8; (WIDTH * HEIGHT / 8) as usize],
+}
+
+impl Framebuffer {
+    pub const WIDTH: u32 = WIDTH;
+    pub const HEIGHT: u32 = HEIGHT;
+
+    /// A blank (all off) frame
+    pub fn new() -> Self {
+        Self {
+            bits: [0; (WIDTH * HEIGHT / 8) as usize],
+        }
+    }
+
+    /// Whether the pixel at `(x, y)` is lit; false outside the frame
+    pub fn pixel(&self, x: u32, y: u32) -> bool {
+        if x >= WIDTH || y >= HEIGHT {
+            return false;
+        }
+        let i = (y * WIDTH + x) as usize;
+        self.bits[i / 8] & (0x80 >> (i % 8)) != 0
+    }
+
+    /// The packed pixels, most significant bit first
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bits
+    }
+}
+
+impl Default for Framebuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OriginDimensions for Framebuffer {
+    fn size(&self) -> Size {
+        Size::new(WIDTH, HEIGHT)
+    }
+}
+
+impl DrawTarget for Framebuffer {
+    type Color = BinaryColor;
+    type Error = Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where
+        I: IntoIterator<Item = Pixel<Self::Color>>,
+    {
+        for Pixel(point, color) in pixels {
+            let (Ok(x), Ok(y)) = (u32::try_from(point.x), u32::try_from(point.y)) else {
+                continue;
+            };
+            if x >= WIDTH || y >= HEIGHT {
+                continue;
+            }
+            let i = (y * WIDTH + x) as usize;
+            let mask = 0x80 >> (i % 8);
+            if color.is_on() {
+                self.bits[i / 8] |= mask;
+            } else {
+                self.bits[i / 8] &= !mask;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embedded_graphics::primitives::{PrimitiveStyle, Rectangle};
+
+    #[test]
+    fn test_draws_and_clips() {
+        let mut fb = Framebuffer::new();
+        Rectangle::new(Point::new(126, 62), Size::new(4, 4))
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(&mut fb)
+            .unwrap();
+        assert!(fb.pixel(127, 63));
+        assert!(fb.pixel(126, 62));
+        assert!(!fb.pixel(125, 62));
+        assert!(!fb.pixel(128, 63));
+        assert_eq!(fb.as_bytes().iter().map(|b| b.count_ones()).sum::<u32>(), 4);
+    }
+}
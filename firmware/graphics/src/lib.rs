@@ -0,0 +1,13 @@
+//! Tamagochi Classic - Display Rendering
+//!
+//! Draws the game on any 128x64 monochrome `embedded-graphics` target:
+//! the ESP32's SSD1306 OLED, or a [`Framebuffer`] on platforms that turn
+//! the frame into an image.
+
+#![cfg_attr(not(test), no_std)]
+
+mod framebuffer;
+pub mod screen;
+pub mod sprites;
+
+pub use framebuffer::Framebuffer;
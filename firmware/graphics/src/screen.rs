@@ -0,0 +1,133 @@
+//! The main game screen: pet, status bars, warnings and action hints
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{PrimitiveStyle, Rectangle},
+    text::Text,
+};
+use tamagochi_core::{Action, Tamagochi};
+
+use crate::sprites;
+
+/// Draw the main game state
+///
+/// Draws over whatever is on the target; clear it first.
+pub fn draw_game_state(
+    display: &mut impl DrawTarget<Color = BinaryColor>,
+    tama: &Tamagochi,
+    frame: u8,
+) {
+    // Draw sprite in center-top area, rocking side to side while the egg wiggles
+    let wiggle = if tama.is_wiggling() {
+        if frame.is_multiple_of(2) { -3 } else { 3 }
+    } else {
+        0
+    };
+    sprites::draw_mood_sprite(display, tama.stage, tama.mood, frame, Point::new(40 + wiggle, 5));
+
+    // Draw status bars at bottom
+    draw_status_bars(display, tama);
+
+    // Draw warning indicators if needed
+    draw_warnings(display, tama);
+
+    // Show which buttons do something in this stage
+    draw_action_hints(display, tama);
+}
+/// Draw status bars for hunger, happiness, health
+fn draw_status_bars(display: &mut impl DrawTarget<Color = BinaryColor>, tama: &Tamagochi) {
+    let bar_width = 40;
+    let bar_height = 4;
+    let start_y = 52;
+    
+    // Hunger bar (0 = empty, 100 = full, so invert for display)
+    let hunger_fill = ((100 - tama.hunger) as i32 * bar_width) / 100;
+    draw_bar(display, Point::new(0, start_y), bar_width, bar_height, hunger_fill);
+    
+    // Happiness bar
+    let happy_fill = (tama.happiness as i32 * bar_width) / 100;
+    draw_bar(display, Point::new(44, start_y), bar_width, bar_height, happy_fill);
+    
+    // Health bar
+    let health_fill = (tama.health as i32 * bar_width) / 100;
+    draw_bar(display, Point::new(88, start_y), bar_width, bar_height, health_fill);
+    
+    // Labels
+    let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    Text::new("F", Point::new(0, 63), text_style).draw(display).ok();
+    Text::new("H", Point::new(44, 63), text_style).draw(display).ok();
+    Text::new("M", Point::new(88, 63), text_style).draw(display).ok();
+}
+
+/// Draw a single status bar
+fn draw_bar(
+    display: &mut impl DrawTarget<Color = BinaryColor>,
+    position: Point,
+    width: i32,
+    height: i32,
+    fill: i32,
+) {
+    // Draw outline
+    Rectangle::new(position, Size::new(width as u32, height as u32))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    
+    // Draw filled portion
+    if fill > 0 {
+        Rectangle::new(
+            position + Point::new(1, 1),
+            Size::new((fill - 2).max(0) as u32, (height - 2) as u32),
+        )
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(display)
+        .ok();
+    }
+}
+
+/// Draw warning indicators
+fn draw_warnings(display: &mut impl DrawTarget<Color = BinaryColor>, tama: &Tamagochi) {
+    let needs = tama.needs_status();
+    let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    
+    let y = 42;
+    if needs.hunger_critical {
+        Text::new("HUNGRY!", Point::new(2, y), text_style).draw(display).ok();
+    }
+    if needs.happiness_critical {
+        Text::new("SAD!", Point::new(50, y), text_style).draw(display).ok();
+    }
+    if needs.health_critical {
+        Text::new("SICK!", Point::new(90, y), text_style).draw(display).ok();
+    }
+    if tama.is_wiggling() {
+        Text::new("WARM ME!", Point::new(2, y), text_style).draw(display).ok();
+    }
+}
+
+/// Draw the letters of available actions down the right edge
+fn draw_action_hints(display: &mut impl DrawTarget<Color = BinaryColor>, tama: &Tamagochi) {
+    let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+
+    for (i, action) in Action::BUTTONS.iter().enumerate() {
+        if tama.can_perform(*action) {
+            let letter = &action_label(*action)[..1];
+            Text::new(letter, Point::new(122, 10 + i as i32 * 10), text_style)
+                .draw(display)
+                .ok();
+        }
+    }
+}
+
+/// Feedback text for an action
+pub fn action_label(action: Action) -> &'static str {
+    match action {
+        Action::Feed => "FEED!",
+        Action::Play => "PLAY!",
+        Action::Medicine => "MEDICINE!",
+        Action::Clean => "CLEAN!",
+        Action::Warm => "WARM!",
+    }
+}
@@ -0,0 +1,621 @@
+//! Sprite rendering for the 128x64 monochrome display
+//! Converts ASCII art to pixel graphics
+
+use embedded_graphics::{
+    mono_font::{ascii::FONT_6X10, MonoTextStyle},
+    pixelcolor::BinaryColor,
+    prelude::*,
+    primitives::{Circle, Line, PrimitiveStyle, Rectangle},
+    text::Text,
+};
+use tamagochi_core::minigame::{Direction, MiniGame, Phase, ROUNDS};
+use tamagochi_core::qr::QUIET_ZONE;
+use tamagochi_core::{LifeStage, Mood, Passcode};
+
+/// Draw sprite at given position
+pub fn draw_sprite(
+    display: &mut impl DrawTarget<Color = BinaryColor>,
+    stage: LifeStage,
+    frame: u8,
+    position: Point,
+) {
+    match stage {
+        LifeStage::Egg => draw_egg(display, position, frame),
+        LifeStage::Baby => draw_baby(display, position, frame),
+        LifeStage::Child => draw_child(display, position, frame),
+        LifeStage::Teenager => draw_teenager(display, position, frame),
+        LifeStage::Adult(form) => draw_adult(display, position, frame, form),
+        LifeStage::Elder => draw_elder(display, position, frame),
+    }
+}
+
+/// Draw sprite showing the given mood
+///
+/// Content pets use the regular animation. For other moods the stage's
+/// face is blanked and redrawn with mood eyes, mouth and a small
+/// decoration next to the head.
+pub fn draw_mood_sprite(
+    display: &mut impl DrawTarget<Color = BinaryColor>,
+    stage: LifeStage,
+    mood: Mood,
+    frame: u8,
+    position: Point,
+) {
+    draw_sprite(display, stage, frame, position);
+    if mood == Mood::Content {
+        return;
+    }
+
+    let (left_eye, right_eye, mouth_y) = face_layout(stage);
+    let pos = position;
+    let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+    let fill = PrimitiveStyle::with_fill(BinaryColor::On);
+
+    // Blank the regular face
+    Rectangle::new(
+        pos + left_eye - Point::new(1, 2),
+        Size::new((right_eye.x - left_eye.x + 5) as u32, (mouth_y - left_eye.y + 4) as u32),
+    )
+    .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+    .draw(display)
+    .ok();
+
+    // Eyes
+    for eye in [left_eye, right_eye] {
+        let eye = pos + eye;
+        match mood {
+            Mood::Sick => {
+                // x eyes
+                Line::new(eye + Point::new(-1, -1), eye + Point::new(2, 2)).into_styled(stroke).draw(display).ok();
+                Line::new(eye + Point::new(2, -1), eye + Point::new(-1, 2)).into_styled(stroke).draw(display).ok();
+            }
+            Mood::Angry => {
+                // Slanted brows over small eyes
+                let (from, to) = if eye.x < pos.x + 9 {
+                    (Point::new(-1, -2), Point::new(2, -1))
+                } else {
+                    (Point::new(-1, -1), Point::new(2, -2))
+                };
+                Line::new(eye + from, eye + to).into_styled(stroke).draw(display).ok();
+                Rectangle::new(eye + Point::new(0, 1), Size::new(2, 1)).into_styled(fill).draw(display).ok();
+            }
+            Mood::Sad => {
+                // Eyes with a tear
+                Circle::new(eye, 2).into_styled(fill).draw(display).ok();
+                if frame % 4 < 2 {
+                    Line::new(eye + Point::new(0, 3), eye + Point::new(0, 4)).into_styled(stroke).draw(display).ok();
+                }
+            }
+            Mood::Happy => {
+                // Smiling ^ eyes
+                Line::new(eye + Point::new(-1, 1), eye).into_styled(stroke).draw(display).ok();
+                Line::new(eye + Point::new(1, 0), eye + Point::new(2, 1)).into_styled(stroke).draw(display).ok();
+            }
+            Mood::Sleepy | Mood::Bored => {
+                // Closed or half-closed eyes
+                Line::new(eye + Point::new(-1, 1), eye + Point::new(2, 1)).into_styled(stroke).draw(display).ok();
+            }
+            _ => {
+                Circle::new(eye, 2).into_styled(fill).draw(display).ok();
+            }
+        }
+    }
+
+    // Mouth
+    let mouth_left = pos + Point::new(left_eye.x + 1, mouth_y);
+    let mouth_right = pos + Point::new(right_eye.x + 1, mouth_y);
+    let mouth_mid = Point::new((mouth_left.x + mouth_right.x) / 2, mouth_y + pos.y);
+    match mood {
+        Mood::Hungry => {
+            // Wide open mouth
+            Circle::new(mouth_mid - Point::new(2, 2), if frame.is_multiple_of(2) { 4 } else { 3 })
+                .into_styled(stroke)
+                .draw(display)
+                .ok();
+        }
+        Mood::Sad | Mood::Sick => {
+            // Frown
+            Line::new(mouth_left + Point::new(0, 1), mouth_mid).into_styled(stroke).draw(display).ok();
+            Line::new(mouth_mid, mouth_right + Point::new(0, 1)).into_styled(stroke).draw(display).ok();
+        }
+        Mood::Happy => {
+            // Wide smile
+            Line::new(mouth_left - Point::new(1, 1), mouth_mid + Point::new(0, 1)).into_styled(stroke).draw(display).ok();
+            Line::new(mouth_mid + Point::new(0, 1), mouth_right + Point::new(1, -1)).into_styled(stroke).draw(display).ok();
+        }
+        Mood::Angry => {
+            // Zig-zag
+            Line::new(mouth_left, mouth_mid + Point::new(0, 1)).into_styled(stroke).draw(display).ok();
+            Line::new(mouth_mid + Point::new(0, 1), mouth_right).into_styled(stroke).draw(display).ok();
+        }
+        _ => {
+            // Flat
+            Line::new(mouth_left + Point::new(1, 0), mouth_right - Point::new(1, 0))
+                .into_styled(stroke)
+                .draw(display)
+                .ok();
+        }
+    }
+
+    // Decoration beside the head
+    let side = pos + Point::new(right_eye.x + 8, 0);
+    match mood {
+        Mood::Sleepy => {
+            // Floating "z"
+            let z = side + Point::new(0, -((frame % 4) as i32));
+            Line::new(z, z + Point::new(4, 0)).into_styled(stroke).draw(display).ok();
+            Line::new(z + Point::new(4, 0), z + Point::new(0, 4)).into_styled(stroke).draw(display).ok();
+            Line::new(z + Point::new(0, 4), z + Point::new(4, 4)).into_styled(stroke).draw(display).ok();
+        }
+        Mood::Angry => {
+            // Anger mark
+            Line::new(side + Point::new(0, 2), side + Point::new(4, 2)).into_styled(stroke).draw(display).ok();
+            Line::new(side + Point::new(2, 0), side + Point::new(2, 4)).into_styled(stroke).draw(display).ok();
+        }
+        Mood::Sick => {
+            // Sweat drop
+            Circle::new(side + Point::new(0, 4), 3).into_styled(stroke).draw(display).ok();
+        }
+        Mood::Hungry if frame.is_multiple_of(2) => {
+            // Drool
+            Line::new(mouth_mid + Point::new(2, 2), mouth_mid + Point::new(2, 5)).into_styled(stroke).draw(display).ok();
+        }
+        _ => {}
+    }
+}
+
+/// Eye positions and mouth height for each stage, relative to the sprite origin
+fn face_layout(stage: LifeStage) -> (Point, Point, i32) {
+    match stage {
+        LifeStage::Egg => (Point::new(5, 8), Point::new(13, 8), 14),
+        LifeStage::Baby => (Point::new(4, 6), Point::new(10, 6), 11),
+        _ => (Point::new(5, 7), Point::new(11, 7), 13),
+    }
+}
+
+/// Draw egg sprite (simple oval with animation)
+fn draw_egg(display: &mut impl DrawTarget<Color = BinaryColor>, pos: Point, frame: u8) {
+    // Egg body (oval approximation)
+    Circle::new(pos, 20)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+    
+    // Eyes that blink
+    let eye_y = if frame % 10 < 8 { 2 } else { 0 }; // Blink occasionally
+    
+    if eye_y > 0 {
+        // Left eye
+        Circle::new(pos + Point::new(5, 8), 2)
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(display)
+            .ok();
+        
+        // Right eye
+        Circle::new(pos + Point::new(13, 8), 2)
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(display)
+            .ok();
+    } else {
+        // Closed eyes (lines)
+        Line::new(pos + Point::new(5, 9), pos + Point::new(7, 9))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .ok();
+        Line::new(pos + Point::new(13, 9), pos + Point::new(15, 9))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .ok();
+    }
+}
+
+/// Number of steps in the hatching animation
+pub const HATCHING_STEPS: u8 = 4;
+
+/// Draw one step of the hatching animation
+///
+/// The shell cracks over the first steps, then splits open to reveal the baby.
+pub fn draw_hatching(display: &mut impl DrawTarget<Color = BinaryColor>, step: u8, pos: Point) {
+    let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+
+    if step + 1 < HATCHING_STEPS {
+        draw_egg(display, pos, 0);
+
+        // Zig-zag crack that grows with every step
+        let crack = [
+            (Point::new(3, 10), Point::new(7, 13)),
+            (Point::new(7, 13), Point::new(10, 9)),
+            (Point::new(10, 9), Point::new(13, 13)),
+            (Point::new(13, 13), Point::new(17, 10)),
+        ];
+        for (start, end) in crack.iter().take((step as usize + 1) * 2) {
+            Line::new(pos + *start, pos + *end)
+                .into_styled(stroke)
+                .draw(display)
+                .ok();
+        }
+    } else {
+        // Shell halves fall apart, baby peeks out
+        draw_baby(display, pos + Point::new(2, -2), 0);
+        Line::new(pos + Point::new(-4, 26), pos + Point::new(24, 26))
+            .into_styled(stroke)
+            .draw(display)
+            .ok();
+        Line::new(pos + Point::new(-4, 26), pos + Point::new(-2, 20))
+            .into_styled(stroke)
+            .draw(display)
+            .ok();
+        Line::new(pos + Point::new(24, 26), pos + Point::new(22, 20))
+            .into_styled(stroke)
+            .draw(display)
+            .ok();
+    }
+}
+
+/// Draw baby sprite
+fn draw_baby(display: &mut impl DrawTarget<Color = BinaryColor>, pos: Point, frame: u8) {
+    // Head
+    Circle::new(pos, 16)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+    
+    // Ears
+    Circle::new(pos + Point::new(-2, 2), 4)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    Circle::new(pos + Point::new(14, 2), 4)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    
+    // Eyes with animation
+    let eye_offset = match frame % 4 {
+        0 => 0,
+        1 => 1,
+        2 => 0,
+        _ => -1,
+    };
+    
+    Circle::new(pos + Point::new(4 + eye_offset, 6), 2)
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(display)
+        .ok();
+    Circle::new(pos + Point::new(10 + eye_offset, 6), 2)
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(display)
+        .ok();
+    
+    // Mouth (smile)
+    Line::new(pos + Point::new(5, 11), pos + Point::new(11, 11))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    
+    // Body (small)
+    Rectangle::new(pos + Point::new(4, 18), Size::new(8, 8))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+}
+
+/// Draw child sprite (bigger than baby)
+fn draw_child(display: &mut impl DrawTarget<Color = BinaryColor>, pos: Point, frame: u8) {
+    // Head
+    Circle::new(pos, 18)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+    
+    // Ears
+    Circle::new(pos + Point::new(-2, 4), 5)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    Circle::new(pos + Point::new(15, 4), 5)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    
+    // Animated eyes
+    let eye_state = frame % 3;
+    match eye_state {
+        0 => {
+            // Normal eyes
+            Circle::new(pos + Point::new(5, 7), 2)
+                .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                .draw(display)
+                .ok();
+            Circle::new(pos + Point::new(11, 7), 2)
+                .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                .draw(display)
+                .ok();
+        }
+        1 => {
+            // Happy eyes (^_^)
+            Line::new(pos + Point::new(4, 7), pos + Point::new(6, 9))
+                .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                .draw(display)
+                .ok();
+            Line::new(pos + Point::new(6, 9), pos + Point::new(7, 7))
+                .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                .draw(display)
+                .ok();
+            
+            Line::new(pos + Point::new(11, 7), pos + Point::new(12, 9))
+                .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                .draw(display)
+                .ok();
+            Line::new(pos + Point::new(12, 9), pos + Point::new(14, 7))
+                .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                .draw(display)
+                .ok();
+        }
+        _ => {
+            // Wink
+            Circle::new(pos + Point::new(5, 7), 2)
+                .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+                .draw(display)
+                .ok();
+            Line::new(pos + Point::new(11, 8), pos + Point::new(13, 8))
+                .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+                .draw(display)
+                .ok();
+        }
+    }
+    
+    // Mouth
+    Line::new(pos + Point::new(6, 13), pos + Point::new(12, 13))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    
+    // Body (larger)
+    Rectangle::new(pos + Point::new(3, 20), Size::new(12, 10))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+}
+
+/// Draw teenager sprite
+fn draw_teenager(display: &mut impl DrawTarget<Color = BinaryColor>, pos: Point, frame: u8) {
+    // Similar to child but taller
+    draw_child(display, pos, frame);
+    
+    // Add feet
+    Line::new(pos + Point::new(5, 32), pos + Point::new(5, 35))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+    Line::new(pos + Point::new(13, 32), pos + Point::new(13, 35))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+}
+
+/// Draw adult sprite (varies by form)
+fn draw_adult(
+    display: &mut impl DrawTarget<Color = BinaryColor>,
+    pos: Point,
+    frame: u8,
+    form: tamagochi_core::AdultForm,
+) {
+    use tamagochi_core::AdultForm;
+    
+    match form {
+        AdultForm::Neglected => draw_neglected_adult(display, pos, frame),
+        AdultForm::Normal => draw_normal_adult(display, pos, frame),
+        AdultForm::Premium => draw_premium_adult(display, pos, frame),
+    }
+}
+
+fn draw_neglected_adult(display: &mut impl DrawTarget<Color = BinaryColor>, pos: Point, frame: u8) {
+    // Sad, droopy appearance
+    draw_teenager(display, pos, frame);
+    
+    // Sad eyes (x_x)
+    Line::new(pos + Point::new(4, 6), pos + Point::new(7, 9))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    Line::new(pos + Point::new(7, 6), pos + Point::new(4, 9))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    
+    Line::new(pos + Point::new(11, 6), pos + Point::new(14, 9))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    Line::new(pos + Point::new(14, 6), pos + Point::new(11, 9))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+}
+
+fn draw_normal_adult(display: &mut impl DrawTarget<Color = BinaryColor>, pos: Point, frame: u8) {
+    // Normal healthy adult
+    draw_teenager(display, pos, frame);
+}
+
+fn draw_premium_adult(display: &mut impl DrawTarget<Color = BinaryColor>, pos: Point, frame: u8) {
+    // Premium with sparkles
+    draw_teenager(display, pos, frame);
+    
+    // Add sparkles around the head
+    if frame.is_multiple_of(2) {
+        // Star left
+        Line::new(pos + Point::new(-5, 5), pos + Point::new(-3, 5))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .ok();
+        Line::new(pos + Point::new(-4, 4), pos + Point::new(-4, 6))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .ok();
+        
+        // Star right
+        Line::new(pos + Point::new(21, 5), pos + Point::new(23, 5))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .ok();
+        Line::new(pos + Point::new(22, 4), pos + Point::new(22, 6))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .ok();
+    }
+}
+
+/// Draw elder sprite (sleepy eyes and a walking cane)
+fn draw_elder(display: &mut impl DrawTarget<Color = BinaryColor>, pos: Point, frame: u8) {
+    // Head
+    Circle::new(pos, 18)
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+
+    // Droopy eyes, opening slowly now and then
+    if frame % 8 < 6 {
+        Line::new(pos + Point::new(4, 8), pos + Point::new(7, 8))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .ok();
+        Line::new(pos + Point::new(11, 8), pos + Point::new(14, 8))
+            .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+            .draw(display)
+            .ok();
+    } else {
+        Circle::new(pos + Point::new(5, 7), 2)
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(display)
+            .ok();
+        Circle::new(pos + Point::new(11, 7), 2)
+            .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+            .draw(display)
+            .ok();
+    }
+
+    // Mouth
+    Line::new(pos + Point::new(7, 13), pos + Point::new(11, 13))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+
+    // Slightly hunched body
+    Rectangle::new(pos + Point::new(2, 20), Size::new(13, 10))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+
+    // Feet
+    Line::new(pos + Point::new(5, 32), pos + Point::new(5, 35))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+    Line::new(pos + Point::new(12, 32), pos + Point::new(12, 35))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 2))
+        .draw(display)
+        .ok();
+
+    // Cane
+    Line::new(pos + Point::new(20, 20), pos + Point::new(20, 35))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+    Line::new(pos + Point::new(17, 20), pos + Point::new(20, 20))
+        .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 1))
+        .draw(display)
+        .ok();
+}
+
+/// Draw a running mini-game
+///
+/// The pet stays in the middle; arrows, prompts and the score are drawn
+/// around it depending on the game phase.
+pub fn draw_minigame(
+    display: &mut impl DrawTarget<Color = BinaryColor>,
+    game: &MiniGame,
+    stage: LifeStage,
+    frame: u8,
+) {
+    let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 2);
+
+    // Pet, shifted towards the side it turned to
+    let offset = match game.phase() {
+        Phase::Reveal { turned: Direction::Left, .. } => -20,
+        Phase::Reveal { turned: Direction::Right, .. } => 20,
+        _ => 0,
+    };
+    draw_sprite(display, stage, frame, Point::new(54 + offset, 8));
+
+    // Score line
+    let mut score: heapless::String<16> = heapless::String::new();
+    core::fmt::write(&mut score, format_args!("{}/{} WON {}", game.round(), ROUNDS, game.wins())).ok();
+    Text::new(&score, Point::new(0, 8), text_style).draw(display).ok();
+
+    let prompt = match game.phase() {
+        Phase::Choose => {
+            // Arrows on both sides
+            Line::new(Point::new(4, 30), Point::new(14, 24)).into_styled(stroke).draw(display).ok();
+            Line::new(Point::new(4, 30), Point::new(14, 36)).into_styled(stroke).draw(display).ok();
+            Line::new(Point::new(123, 30), Point::new(113, 24)).into_styled(stroke).draw(display).ok();
+            Line::new(Point::new(123, 30), Point::new(113, 36)).into_styled(stroke).draw(display).ok();
+            "LEFT OR RIGHT?"
+        }
+        Phase::Reveal { won: true, .. } | Phase::RoundOver { won: true } => "YES!",
+        Phase::Reveal { won: false, .. } | Phase::RoundOver { won: false } => "MISSED",
+        Phase::Wait => "WAIT...",
+        Phase::Go => {
+            Rectangle::new(Point::new(0, 0), Size::new(128, 64))
+                .into_styled(PrimitiveStyle::with_stroke(BinaryColor::On, 3))
+                .draw(display)
+                .ok();
+            "GO!"
+        }
+        Phase::Finished(_) => "DONE",
+    };
+    Text::new(prompt, Point::new(30, 60), text_style).draw(display).ok();
+}
+
+/// Pixels per QR module; 2 fits the code with its quiet zone in 58 rows
+const QR_SCALE: i32 = 2;
+
+/// Draw a passcode as a QR code on the left with its text on the right
+///
+/// OLED pixels glow, so the light modules and the quiet zone are lit and
+/// the dark modules stay off, the way scanners expect.
+pub fn draw_passcode(display: &mut impl DrawTarget<Color = BinaryColor>, code: &Passcode) {
+    let qr = code.to_qr();
+    let margin = QUIET_ZONE as i32;
+    let modules = qr.size() as i32 + 2 * margin;
+    let origin = Point::new(0, (64 - modules * QR_SCALE) / 2);
+
+    Rectangle::new(origin, Size::new_equal((modules * QR_SCALE) as u32))
+        .into_styled(PrimitiveStyle::with_fill(BinaryColor::On))
+        .draw(display)
+        .ok();
+    for y in 0..qr.size() {
+        for x in 0..qr.size() {
+            if qr.module(x, y) {
+                let module = Point::new(x as i32 + margin, y as i32 + margin) * QR_SCALE;
+                Rectangle::new(origin + module, Size::new_equal(QR_SCALE as u32))
+                    .into_styled(PrimitiveStyle::with_fill(BinaryColor::Off))
+                    .draw(display)
+                    .ok();
+            }
+        }
+    }
+
+    // The code in groups of four, for typing it in by hand
+    let text_style = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let text_x = modules * QR_SCALE + 8;
+    Text::new("CODE", Point::new(text_x, 10), text_style).draw(display).ok();
+    for (i, group) in code.as_str().as_bytes().chunks(4).enumerate() {
+        let group = core::str::from_utf8(group).unwrap_or("");
+        Text::new(group, Point::new(text_x + 12, 24 + i as i32 * 11), text_style)
+            .draw(display)
+            .ok();
+    }
+}
@@ -0,0 +1,405 @@
+//! Python bindings for the core
+//!
+//! A PyO3 module to study the game's balance from Python or Jupyter with
+//! the real rules:
+//!
+//! ```python
+//! import tamagochi
+//!
+//! pet = tamagochi.Tamagochi()
+//! pet.warm()
+//! for event in pet.advance(3600):
+//!     print(event.tick, event.kind)
+//! print(pet.stage, pet.hunger, pet.health)
+//!
+//! # A week of three pets, checked on every 5 ticks, every minute, never
+//! runs = tamagochi.simulate([5, 60, None], ticks=7 * 86400)
+//! plt.plot(runs[1]["tick"], runs[1]["health"])
+//! ```
+//!
+//! Build it into the active virtualenv with `maturin develop` from this
+//! directory. Stages and moods are lowercase strings (`"egg"`,
+//! `"adult_premium"`, `"hungry"`, ...).
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use tamagochi_core::{Action, AdultForm, GameEvent, LifeStage, Mood};
+
+/// A pet
+#[pyclass(module = "tamagochi", skip_from_py_object)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tamagochi {
+    inner: tamagochi_core::Tamagochi,
+}
+
+#[pymethods]
+impl Tamagochi {
+    /// A fresh egg
+    #[new]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fresh egg of the next generation
+    pub fn next_generation(&self) -> Self {
+        Self {
+            inner: self.inner.next_generation(),
+        }
+    }
+
+    /// Load a pet from the binary save format
+    #[staticmethod]
+    pub fn load(data: &[u8]) -> PyResult<Self> {
+        tamagochi_core::Tamagochi::from_bytes(data)
+            .map(|inner| Self { inner })
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    /// Encode the pet into the binary save format
+    pub fn save<'py>(&self, py: Python<'py>) -> Bound<'py, PyBytes> {
+        PyBytes::new(py, &self.inner.to_bytes())
+    }
+
+    /// Feed the pet; False if it can't eat right now
+    pub fn feed(&mut self) -> bool {
+        self.inner.perform(Action::Feed)
+    }
+
+    /// Play with the pet; False if it can't play right now
+    pub fn play(&mut self) -> bool {
+        self.inner.perform(Action::Play)
+    }
+
+    /// Give medicine; False if it can't take any right now
+    pub fn medicine(&mut self) -> bool {
+        self.inner.perform(Action::Medicine)
+    }
+
+    /// Clean up after the pet; False if there's nothing to clean
+    pub fn clean(&mut self) -> bool {
+        self.inner.perform(Action::Clean)
+    }
+
+    /// Warm the egg; False if it isn't an egg
+    pub fn warm(&mut self) -> bool {
+        self.inner.perform(Action::Warm)
+    }
+
+    /// Perform an action by name ("feed", "play", ...)
+    pub fn perform(&mut self, action: &str) -> PyResult<bool> {
+        Ok(self.inner.perform(parse_action(action)?))
+    }
+
+    /// Whether an action is available right now
+    pub fn can_perform(&self, action: &str) -> PyResult<bool> {
+        Ok(self.inner.can_perform(parse_action(action)?))
+    }
+
+    /// Run one tick (a second of pet time), returning its event if any
+    pub fn update(&mut self) -> Option<Event> {
+        let event = self.inner.update()?;
+        Some(Event::new(event, &self.inner))
+    }
+
+    /// Run several ticks, returning their events; stops early if the pet
+    /// dies
+    pub fn advance(&mut self, ticks: u32) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.inner.advance(ticks, |event, pet| events.push(Event::new(event, pet)));
+        events
+    }
+
+    /// 0 = full, 100 = starving
+    #[getter]
+    pub fn hunger(&self) -> u8 {
+        self.inner.hunger
+    }
+
+    #[getter]
+    pub fn happiness(&self) -> u8 {
+        self.inner.happiness
+    }
+
+    #[getter]
+    pub fn health(&self) -> u8 {
+        self.inner.health
+    }
+
+    /// Grams
+    #[getter]
+    pub fn weight(&self) -> u8 {
+        self.inner.weight
+    }
+
+    /// Age in ticks
+    #[getter]
+    pub fn age(&self) -> u32 {
+        self.inner.age
+    }
+
+    #[getter]
+    pub fn age_days(&self) -> u32 {
+        self.inner.status_summary().age_days
+    }
+
+    #[getter]
+    pub fn alive(&self) -> bool {
+        self.inner.alive
+    }
+
+    #[getter]
+    pub fn stage(&self) -> &'static str {
+        stage_name(self.inner.stage)
+    }
+
+    #[getter]
+    pub fn mood(&self) -> &'static str {
+        mood_name(self.inner.mood)
+    }
+
+    /// 0 to 100; decides the adult form and the lifespan
+    #[getter]
+    pub fn care_quality(&self) -> u8 {
+        self.inner.care_quality
+    }
+
+    #[getter]
+    pub fn care_mistakes(&self) -> u8 {
+        self.inner.care_mistakes
+    }
+
+    #[getter]
+    pub fn generation(&self) -> u16 {
+        self.inner.generation
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Tamagochi(stage='{}', age={}, hunger={}, happiness={}, health={}, alive={})",
+            self.stage(),
+            self.inner.age,
+            self.inner.hunger,
+            self.inner.happiness,
+            self.inner.health,
+            if self.inner.alive { "True" } else { "False" },
+        )
+    }
+}
+
+/// Something that happened during a tick
+#[pyclass(module = "tamagochi", frozen, eq, get_all, skip_from_py_object)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    /// The pet's age in ticks when it happened
+    tick: u32,
+    /// "hatched", "evolution", "death", "passed_away", "needs_attention"
+    /// or "birthday"
+    kind: &'static str,
+    /// The new stage for "evolution", else None
+    stage: Option<&'static str>,
+    /// The age in days for "birthday", else None
+    days: Option<u32>,
+}
+
+impl Event {
+    fn new(event: GameEvent, pet: &tamagochi_core::Tamagochi) -> Self {
+        let (kind, stage, days) = match event {
+            GameEvent::Hatched => ("hatched", None, None),
+            GameEvent::Evolution(stage) => ("evolution", Some(stage_name(stage)), None),
+            GameEvent::Death => ("death", None, None),
+            GameEvent::PassedAway => ("passed_away", None, None),
+            GameEvent::NeedsAttention => ("needs_attention", None, None),
+            GameEvent::Birthday(days) => ("birthday", None, Some(days)),
+        };
+        Self {
+            tick: pet.age,
+            kind,
+            stage,
+            days,
+        }
+    }
+}
+
+#[pymethods]
+impl Event {
+    fn __repr__(&self) -> String {
+        let detail = match (self.stage, self.days) {
+            (Some(stage), _) => format!(", stage='{}'", stage),
+            (_, Some(days)) => format!(", days={}", days),
+            _ => String::new(),
+        };
+        format!("Event(tick={}, kind='{}'{})", self.tick, self.kind, detail)
+    }
+}
+
+/// One pet's run in `simulate`, a dict of equally long lists plus the
+/// events
+#[derive(Debug, Default, IntoPyObject)]
+struct Timeline {
+    tick: Vec<u32>,
+    hunger: Vec<u32>,
+    happiness: Vec<u32>,
+    health: Vec<u32>,
+    weight: Vec<u32>,
+    stage: Vec<&'static str>,
+    mood: Vec<&'static str>,
+    events: Vec<Event>,
+}
+
+impl Timeline {
+    fn sample(&mut self, pet: &tamagochi_core::Tamagochi) {
+        self.tick.push(pet.age);
+        self.hunger.push(pet.hunger.into());
+        self.happiness.push(pet.happiness.into());
+        self.health.push(pet.health.into());
+        self.weight.push(pet.weight.into());
+        self.stage.push(stage_name(pet.stage));
+        self.mood.push(mood_name(pet.mood));
+    }
+}
+
+/// Raise one fresh egg per entry of `check_every` for `ticks` ticks and
+/// return their stat timelines
+///
+/// Each entry is how often, in ticks, a caretaker checks on that pet:
+/// warming the egg, feeding when hunger reaches 60, playing when
+/// happiness drops to 40 and giving medicine when health drops to 50.
+/// None leaves the pet alone. Stats are sampled every `sample_every`
+/// ticks and once more at the end; a pet's run ends when it dies.
+///
+/// Returns one dict per pet with the lists "tick", "hunger",
+/// "happiness", "health", "weight", "stage" and "mood", and "events", a
+/// list of every Event.
+#[pyfunction]
+#[pyo3(signature = (check_every, ticks, sample_every = 60))]
+fn simulate(py: Python<'_>, check_every: Vec<Option<u32>>, ticks: u32, sample_every: u32) -> PyResult<Vec<Timeline>> {
+    if sample_every == 0 || check_every.contains(&Some(0)) {
+        return Err(PyValueError::new_err("intervals must be at least one tick"));
+    }
+    Ok(py.detach(|| {
+        check_every
+            .iter()
+            .map(|&check| raise(check, ticks, sample_every))
+            .collect()
+    }))
+}
+
+fn raise(check_every: Option<u32>, ticks: u32, sample_every: u32) -> Timeline {
+    let mut pet = tamagochi_core::Tamagochi::new();
+    let mut timeline = Timeline::default();
+    for tick in 0..ticks {
+        if !pet.alive {
+            break;
+        }
+        if check_every.is_some_and(|every| tick.is_multiple_of(every)) {
+            look_after(&mut pet);
+        }
+        if tick.is_multiple_of(sample_every) {
+            timeline.sample(&pet);
+        }
+        if let Some(event) = pet.update() {
+            timeline.events.push(Event::new(event, &pet));
+        }
+    }
+    timeline.sample(&pet);
+    timeline
+}
+
+/// What the caretaker in `simulate` does when checking on a pet
+fn look_after(pet: &mut tamagochi_core::Tamagochi) {
+    if pet.stage == LifeStage::Egg {
+        pet.perform(Action::Warm);
+        return;
+    }
+    if pet.hunger >= 60 {
+        pet.perform(Action::Feed);
+    }
+    if pet.happiness <= 40 {
+        pet.perform(Action::Play);
+    }
+    if pet.health <= 50 {
+        pet.perform(Action::Medicine);
+    }
+}
+
+fn parse_action(name: &str) -> PyResult<Action> {
+    match name {
+        "feed" => Ok(Action::Feed),
+        "play" => Ok(Action::Play),
+        "medicine" => Ok(Action::Medicine),
+        "clean" => Ok(Action::Clean),
+        "warm" => Ok(Action::Warm),
+        _ => Err(PyValueError::new_err(format!("unknown action '{}'", name))),
+    }
+}
+
+fn stage_name(stage: LifeStage) -> &'static str {
+    match stage {
+        LifeStage::Egg => "egg",
+        LifeStage::Baby => "baby",
+        LifeStage::Child => "child",
+        LifeStage::Teenager => "teenager",
+        LifeStage::Adult(AdultForm::Neglected) => "adult_neglected",
+        LifeStage::Adult(AdultForm::Normal) => "adult_normal",
+        LifeStage::Adult(AdultForm::Premium) => "adult_premium",
+        LifeStage::Elder => "elder",
+    }
+}
+
+fn mood_name(mood: Mood) -> &'static str {
+    match mood {
+        Mood::Sick => "sick",
+        Mood::Angry => "angry",
+        Mood::Hungry => "hungry",
+        Mood::Sad => "sad",
+        Mood::Sleepy => "sleepy",
+        Mood::Bored => "bored",
+        Mood::Content => "content",
+        Mood::Happy => "happy",
+    }
+}
+
+#[pymodule]
+fn tamagochi(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<Tamagochi>()?;
+    m.add_class::<Event>()?;
+    m.add_function(wrap_pyfunction!(simulate, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_care_keeps_a_pet_alive() {
+        let day = 86_400;
+        let cared = raise(Some(5), day, 3600);
+        let neglected = raise(Some(60), day, 3600);
+
+        assert_eq!(cared.tick.len(), 25);
+        assert_eq!(cared.tick[..3], [0, 3600, 7200]);
+        for list in [&cared.hunger, &cared.happiness, &cared.health, &cared.weight] {
+            assert_eq!(list.len(), cared.tick.len());
+        }
+        assert_eq!(cared.events[0].kind, "hatched");
+        assert_eq!(cared.stage.last(), Some(&"child"));
+        assert!(cared.events.iter().all(|event| event.kind != "death"));
+
+        let death = neglected.events.last().unwrap();
+        assert_eq!(death.kind, "death");
+        assert_eq!(*neglected.tick.last().unwrap(), death.tick);
+        assert_eq!(*neglected.health.last().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_events_carry_their_detail() {
+        let pet = tamagochi_core::Tamagochi::new();
+        let evolution = Event::new(GameEvent::Evolution(LifeStage::Adult(AdultForm::Premium)), &pet);
+        assert_eq!(evolution.stage, Some("adult_premium"));
+        assert_eq!(evolution.__repr__(), "Event(tick=0, kind='evolution', stage='adult_premium')");
+        assert_eq!(Event::new(GameEvent::Birthday(3), &pet).days, Some(3));
+        assert!(parse_action("feed").is_ok());
+    }
+}
@@ -0,0 +1,285 @@
+//! WebAssembly bindings for the core
+//!
+//! Exposes the [`Tamagochi`] to JavaScript with wasm-bindgen, so the same
+//! game logic runs in a browser or in Node:
+//!
+//! ```js
+//! import init, { Tamagochi } from "./pkg/tamagochi_wasm.js";
+//!
+//! await init();
+//! const pet = new Tamagochi();
+//! pet.warm();
+//! for (const event of pet.advance(3600)) console.log(event);
+//! console.log(pet.status().hunger_level);
+//! localStorage.setItem("pet", btoa(String.fromCharCode(...pet.save())));
+//! ```
+//!
+//! Statuses and events are plain objects shaped like the JSON of the
+//! desktop CLI; their TypeScript types are in `types.d.ts` and end up in
+//! the generated `.d.ts`. Saves are the binary format of
+//! [`tamagochi_core::save`], so they can move between the browser, the
+//! desktop and a device.
+
+use serde::Serialize;
+use serde_wasm_bindgen::Serializer;
+use tamagochi_core::{Action, GameEvent, NeedsStatus, SaveError, StatusSummary};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &str = include_str!("../types.d.ts");
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "Status")]
+    pub type JsStatus;
+    #[wasm_bindgen(typescript_type = "GameEvent")]
+    pub type JsEvent;
+    #[wasm_bindgen(typescript_type = "GameEvent[]")]
+    pub type JsEvents;
+    #[wasm_bindgen(typescript_type = "ActionName")]
+    pub type JsAction;
+}
+
+/// A pet, driven from JavaScript
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tamagochi {
+    inner: tamagochi_core::Tamagochi,
+}
+
+#[wasm_bindgen]
+impl Tamagochi {
+    /// A fresh egg
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A fresh egg of the next generation, once this one has passed away
+    #[wasm_bindgen(js_name = nextGeneration)]
+    pub fn next_generation(&self) -> Self {
+        Self {
+            inner: self.inner.next_generation(),
+        }
+    }
+
+    /// Load a pet from the binary save format
+    pub fn load(bytes: &[u8]) -> Result<Tamagochi, JsError> {
+        decode(bytes)
+            .map(|inner| Self { inner })
+            .map_err(|e| JsError::new(&e.to_string()))
+    }
+
+    /// Encode the pet into the binary save format
+    pub fn save(&self) -> Vec<u8> {
+        self.inner.to_bytes().to_vec()
+    }
+
+    /// Feed the pet; false if it can't eat right now
+    pub fn feed(&mut self) -> bool {
+        self.inner.perform(Action::Feed)
+    }
+
+    /// Play with the pet; false if it can't play right now
+    pub fn play(&mut self) -> bool {
+        self.inner.perform(Action::Play)
+    }
+
+    /// Give medicine; false if it can't take any right now
+    pub fn medicine(&mut self) -> bool {
+        self.inner.perform(Action::Medicine)
+    }
+
+    /// Clean up after the pet; false if there's nothing to clean
+    pub fn clean(&mut self) -> bool {
+        self.inner.perform(Action::Clean)
+    }
+
+    /// Warm the egg; false if it isn't an egg
+    pub fn warm(&mut self) -> bool {
+        self.inner.perform(Action::Warm)
+    }
+
+    /// Perform an action by name (`"feed"`, `"play"`, ...)
+    pub fn perform(&mut self, action: JsAction) -> Result<bool, JsError> {
+        Ok(self.inner.perform(js_action(&action)?))
+    }
+
+    /// Whether an action is available right now
+    #[wasm_bindgen(js_name = canPerform)]
+    pub fn can_perform(&self, action: JsAction) -> Result<bool, JsError> {
+        Ok(self.inner.can_perform(js_action(&action)?))
+    }
+
+    /// Run one tick (a second of pet time), returning its event if any
+    ///
+    /// No event is `undefined`; serializing the `None` would give `null`.
+    pub fn update(&mut self) -> Result<Option<JsEvent>, JsError> {
+        let event = self.inner.update();
+        event.map(|event| Ok(to_js(&event)?.unchecked_into())).transpose()
+    }
+
+    /// Run several ticks, e.g. for time spent in another tab, returning
+    /// their events; stops early if the pet dies
+    pub fn advance(&mut self, ticks: u32) -> Result<JsEvents, JsError> {
+        let events = advance(&mut self.inner, ticks);
+        Ok(to_js(&events)?.unchecked_into())
+    }
+
+    /// Everything about the pet at a glance
+    pub fn status(&self) -> Result<JsStatus, JsError> {
+        Ok(to_js(&Status::new(&self.inner))?.unchecked_into())
+    }
+
+    /// Hunger (0 = full, 100 = starving)
+    #[wasm_bindgen(getter)]
+    pub fn hunger(&self) -> u8 {
+        self.inner.hunger
+    }
+
+    /// Happiness (0 = sad, 100 = happy)
+    #[wasm_bindgen(getter)]
+    pub fn happiness(&self) -> u8 {
+        self.inner.happiness
+    }
+
+    /// Health (0 = dead, 100 = healthy)
+    #[wasm_bindgen(getter)]
+    pub fn health(&self) -> u8 {
+        self.inner.health
+    }
+
+    /// Weight in grams
+    #[wasm_bindgen(getter)]
+    pub fn weight(&self) -> u8 {
+        self.inner.weight
+    }
+
+    /// Age in ticks
+    #[wasm_bindgen(getter)]
+    pub fn age(&self) -> u32 {
+        self.inner.age
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn alive(&self) -> bool {
+        self.inner.alive
+    }
+
+    /// How many pets came before this one
+    #[wasm_bindgen(getter)]
+    pub fn generation(&self) -> u16 {
+        self.inner.generation
+    }
+
+    /// Ticks left until the egg hatches if kept warm, undefined once hatched
+    #[wasm_bindgen(getter, js_name = ticksUntilHatch)]
+    pub fn ticks_until_hatch(&self) -> Option<u32> {
+        self.inner.ticks_until_hatch()
+    }
+}
+
+/// What `status()` returns, matching the `Status` type in `types.d.ts`
+#[derive(Serialize)]
+struct Status {
+    #[serde(flatten)]
+    summary: StatusSummary,
+    needs: NeedsStatus,
+    generation: u16,
+}
+
+impl Status {
+    fn new(pet: &tamagochi_core::Tamagochi) -> Self {
+        Self {
+            summary: pet.status_summary(),
+            needs: pet.needs_status(),
+            generation: pet.generation,
+        }
+    }
+}
+
+fn decode(bytes: &[u8]) -> Result<tamagochi_core::Tamagochi, SaveError> {
+    tamagochi_core::Tamagochi::from_bytes(bytes)
+}
+
+fn advance(pet: &mut tamagochi_core::Tamagochi, ticks: u32) -> Vec<GameEvent> {
+    let mut events = Vec::new();
+    pet.advance(ticks, |event, _| events.push(event));
+    events
+}
+
+fn parse_action(name: &str) -> Option<Action> {
+    match name {
+        "feed" => Some(Action::Feed),
+        "play" => Some(Action::Play),
+        "medicine" => Some(Action::Medicine),
+        "clean" => Some(Action::Clean),
+        "warm" => Some(Action::Warm),
+        _ => None,
+    }
+}
+
+fn js_action(action: &JsAction) -> Result<Action, JsError> {
+    let name = action.as_string().unwrap_or_default();
+    parse_action(&name).ok_or_else(|| JsError::new(&format!("unknown action '{}'", name)))
+}
+
+/// Plain objects, like `JSON.parse` would give for the desktop's JSON
+fn to_js<T: Serialize>(value: &T) -> Result<JsValue, JsError> {
+    value
+        .serialize(&Serializer::json_compatible())
+        .map_err(|e| JsError::new(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tamagochi_core::LifeStage;
+
+    #[test]
+    fn test_actions_and_saves() {
+        let mut pet = Tamagochi::new();
+        assert!(!pet.feed(), "an egg can't eat");
+        assert!(pet.warm());
+        assert_eq!(parse_action("medicine"), Some(Action::Medicine));
+        assert_eq!(parse_action("dance"), None);
+
+        let mut events = Vec::new();
+        while pet.ticks_until_hatch().is_some() {
+            pet.warm();
+            events.extend(advance(&mut pet.inner, 10));
+        }
+        assert!(matches!(events[..], [GameEvent::Hatched]), "{:?}", events);
+        assert_eq!(pet.inner.stage, LifeStage::Baby);
+        assert!(pet.feed());
+
+        let back = decode(&pet.save()).unwrap();
+        assert_eq!(back.age, pet.age());
+        assert_eq!(back.hunger, pet.hunger());
+        assert!(decode(&pet.save()[1..]).is_err());
+    }
+
+    #[test]
+    fn test_quiet_ticks_return_no_event() {
+        // None reaches JavaScript as undefined, serialized it would be null
+        let mut pet = Tamagochi::new();
+        assert!(matches!(pet.update(), Ok(None)));
+    }
+
+    #[test]
+    fn test_status_shape_matches_the_typescript_types() {
+        let mut pet = Tamagochi::new();
+        pet.inner.health = 10;
+        let json = serde_json::to_value(Status::new(&pet.inner)).unwrap();
+        let types = include_str!("../types.d.ts");
+        for key in json.as_object().unwrap().keys() {
+            assert!(types.contains(&format!("  {}: ", key)), "{} isn't in types.d.ts", key);
+        }
+        assert_eq!(json["stage"], "Egg");
+        assert_eq!(json["health_level"], 10);
+        assert_eq!(json["needs"]["health_critical"], true);
+
+        let event = serde_json::to_value(GameEvent::Evolution(LifeStage::Baby)).unwrap();
+        assert_eq!(event, serde_json::json!({ "Evolution": "Baby" }));
+    }
+}
@@ -0,0 +1,45 @@
+export type ActionName = "feed" | "play" | "medicine" | "clean" | "warm";
+
+export type AdultForm = "Neglected" | "Normal" | "Premium";
+
+export type LifeStage =
+  | "Egg"
+  | "Baby"
+  | "Child"
+  | "Teenager"
+  | { Adult: AdultForm }
+  | "Elder";
+
+export type Mood = "Sick" | "Angry" | "Hungry" | "Sad" | "Sleepy" | "Bored" | "Content" | "Happy";
+
+/** Something that happened during a tick */
+export type GameEvent =
+  | "Hatched"
+  | { Evolution: LifeStage }
+  | "Death"
+  | "PassedAway"
+  | "NeedsAttention"
+  | { Birthday: number };
+
+export interface NeedsStatus {
+  hunger_critical: boolean;
+  happiness_critical: boolean;
+  health_critical: boolean;
+}
+
+/** What `Tamagochi.status()` returns */
+export interface Status {
+  alive: boolean;
+  stage: LifeStage;
+  age_days: number;
+  /** 0 = full, 100 = starving */
+  hunger_level: number;
+  happiness_level: number;
+  health_level: number;
+  care_rating: number;
+  /** Grams */
+  weight: number;
+  mood: Mood;
+  needs: NeedsStatus;
+  generation: number;
+}